@@ -52,13 +52,13 @@ use openvm_stark_sdk::{
     config::{
         MAX_APP_LOG_STACKED_HEIGHT, app_params_with_100_bits_security,
         hook_params_with_100_bits_security, internal_params_with_100_bits_security,
-        leaf_params_with_100_bits_security,
     },
     openvm_stark_backend::p3_field::PrimeField32,
 };
 use openvm_verify_stark_circuit::prover::{
     DeferredVerifyCpuCircuitProver as VerifyCircuitProver, DeferredVerifyCpuProver as VerifyProver,
 };
+use scroll_zkvm_types::config::{AggConfig, FD_DEF_HOOK_COMMIT};
 use std::{
     env, fs,
     io::Write,
@@ -71,14 +71,6 @@ mod verifier;
 
 const DIGEST_SIZE: usize = 8;
 
-/// Default aggregation parameters used across chunk, batch, and bundle circuits.
-fn default_agg_params() -> AggregationSystemParams {
-    AggregationSystemParams {
-        leaf: leaf_params_with_100_bits_security(),
-        internal: internal_params_with_100_bits_security(),
-    }
-}
-
 #[derive(Debug, Clone, ValueEnum)]
 enum OutputMode {
     /// Skip generation when output file already exists (default)
@@ -196,7 +188,8 @@ fn generate_app_assets(workspace_dir: &Path, release_output_dir: &PathBuf) -> Re
     println!("{LOG_PREFIX} Projects to build: {:?}", projects_to_build);
 
     let _app_params = app_params_with_100_bits_security(21);
-    let agg_params = default_agg_params();
+    let agg_config = AggConfig::default();
+    let agg_params = agg_config.agg_params().map_err(|e| eyre::eyre!(e))?;
 
     let mut vk_dump: serde_json::Value = serde_json::from_str("{}")?;
     let mut prev_sdk: Option<Sdk> = None;
@@ -259,7 +252,8 @@ fn generate_app_assets(workspace_dir: &Path, release_output_dir: &PathBuf) -> Re
         // Build SDK with the project-specific app config
         let mut sdk_builder = Sdk::builder()
             .app_config(app_config.clone())
-            .agg_params(agg_params.clone());
+            .agg_params(agg_params.clone())
+            .agg_tree_config(release_agg_tree_config(&agg_config));
         if let Some(dp) = deferral_prover {
             sdk_builder = sdk_builder.multi_deferral_circuit_prover(dp);
         }
@@ -371,6 +365,20 @@ fn generate_app_assets(workspace_dir: &Path, release_output_dir: &PathBuf) -> Re
         write_object_to_file(&path_agg_vk, sdk.agg_vk().clone())?;
         println!("{LOG_PREFIX} agg vk written to {path_agg_vk:?}");
 
+        // Record the aggregation config the agg vk was built with, checked by the prover.
+        agg_config.write_to_dir(&path_assets)?;
+        println!("{LOG_PREFIX} agg config written to {path_assets:?}");
+
+        // Dump the deferral hook commit, so that the parent circuit can enable deferral from
+        // the release assets alone.
+        let path_def_hook_commit = path_assets.join(FD_DEF_HOOK_COMMIT);
+        let def_hook_commit: Option<CommitBytes> = sdk.def_hook_commit().map(|c| c.into());
+        fs::write(
+            &path_def_hook_commit,
+            serde_json::to_vec_pretty(&def_hook_commit)?,
+        )?;
+        println!("{LOG_PREFIX} def hook commit written to {path_def_hook_commit:?}");
+
         println!(
             "{LOG_PREFIX} Finished build for config in {:?}",
             start_time.elapsed()
@@ -400,13 +408,29 @@ fn generate_root_verifier(_workspace_dir: &Path, _force_overwrite: bool) -> Resu
     Ok(())
 }
 
-/// Default aggregation-tree shape used by the host prover and the EVM verifier.
-///
-/// Must stay in sync with [`Prover::get_sdk`](crates/prover/src/prover/mod.rs).
-const DEFAULT_AGG_TREE_CONFIG: AggregationTreeConfig = AggregationTreeConfig {
-    num_children_internal: 3,
-    num_children_leaf: 4,
-};
+/// The aggregation tree shape to build the app assets with: that of `agg_config`, which the
+/// prover and the EVM verifier use as well. Prints a warning if it is not the SDK's default
+/// shape, since the agg vks depend on it.
+fn release_agg_tree_config(agg_config: &AggConfig) -> AggregationTreeConfig {
+    let tree = agg_config.agg_tree_config();
+    let sdk_default = AggregationTreeConfig::default();
+    if (tree.num_children_leaf, tree.num_children_internal)
+        != (
+            sdk_default.num_children_leaf,
+            sdk_default.num_children_internal,
+        )
+    {
+        println!(
+            "{LOG_PREFIX} WARNING: aggregation tree of {} leaf / {} internal children differs \
+             from the SDK default of {} / {}, the agg vks differ from those built with the default",
+            tree.num_children_leaf,
+            tree.num_children_internal,
+            sdk_default.num_children_leaf,
+            sdk_default.num_children_internal,
+        );
+    }
+    tree
+}
 
 /// How the EVM verifier should be obtained.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -439,8 +463,10 @@ fn recompute_mode_from_env() -> RecomputeMode {
 fn build_recompute_sdk(
     release_output_dir: &Path,
     app_params: &SystemParams,
-    agg_params: &AggregationSystemParams,
+    agg_config: &AggConfig,
 ) -> Result<Sdk> {
+    let agg_params = &agg_config.agg_params().map_err(|e| eyre::eyre!(e))?;
+
     // 1. Build a batch SDK to serve as the child circuit for bundle's deferral prover.
     let batch_config_path = release_output_dir.join("batch").join(FD_APP_CONFIG);
     let batch_app_config: AppConfig<SdkVmConfig> = if batch_config_path.exists() {
@@ -469,7 +495,7 @@ fn build_recompute_sdk(
     Ok(Sdk::builder()
         .app_config(bundle_app_config)
         .agg_params(agg_params.clone())
-        .agg_tree_config(DEFAULT_AGG_TREE_CONFIG)
+        .agg_tree_config(agg_config.agg_tree_config())
         .multi_deferral_circuit_prover(deferral_prover)
         .build()?)
 }
@@ -483,8 +509,7 @@ pub fn build_evm_verifier(
     release_output_dir: &Path,
 ) -> Result<(Sdk, openvm_sdk::types::EvmHalo2Verifier)> {
     let app_params = app_params_with_100_bits_security(MAX_APP_LOG_STACKED_HEIGHT);
-    let agg_params = default_agg_params();
-    let sdk = build_recompute_sdk(release_output_dir, &app_params, &agg_params)?;
+    let sdk = build_recompute_sdk(release_output_dir, &app_params, &AggConfig::default())?;
     let verifier = sdk.generate_halo2_verifier_solidity()?;
     Ok((sdk, verifier))
 }
@@ -693,7 +718,8 @@ fn generate_evm_verifier(
     }
 
     let app_params = app_params_with_100_bits_security(MAX_APP_LOG_STACKED_HEIGHT);
-    let agg_params = default_agg_params();
+    let agg_config = AggConfig::default();
+    let agg_params = agg_config.agg_params().map_err(|e| eyre::eyre!(e))?;
 
     match recompute_mode {
         RecomputeMode::Yes => {
@@ -738,6 +764,9 @@ fn generate_evm_verifier(
         }
     }
 
+    // Record the aggregation config the verifier was built with, checked by the prover.
+    agg_config.write_to_dir(verifier_output_dir)?;
+
     Ok(())
 }
 
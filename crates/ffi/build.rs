@@ -0,0 +1,22 @@
+//! Generate the C header from the exported functions into `OUT_DIR`. The `header` test checks
+//! that the committed `include/scroll_zkvm.h` matches it, so that the header shipped to the
+//! coordinator always matches the library.
+
+use std::path::PathBuf;
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    let dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("scroll_zkvm.h");
+    // A missing header would only surface as a confusing failure of the `header` test.
+    let config = cbindgen::Config::from_file(dir.join("cbindgen.toml"))
+        .unwrap_or_else(|e| panic!("failed to read cbindgen.toml: {e}"));
+    cbindgen::Builder::new()
+        .with_crate(&dir)
+        .with_config(config)
+        .generate()
+        .unwrap_or_else(|e| panic!("failed to generate the C header: {e}"))
+        .write_to_file(out);
+}
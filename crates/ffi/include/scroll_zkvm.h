@@ -0,0 +1,194 @@
+#ifndef SCROLL_ZKVM_H
+#define SCROLL_ZKVM_H
+
+/* Generated by cbindgen from crates/ffi/src/lib.rs, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Outcome of an exported function.
+ */
+typedef enum ZkvmStatus {
+  /**
+   * Success.
+   */
+  ZKVM_STATUS_OK = 0,
+  /**
+   * A required pointer was null.
+   */
+  ZKVM_STATUS_NULL_POINTER = 1,
+  /**
+   * An input is not valid UTF-8, JSON or hex, or not what the function expects.
+   */
+  ZKVM_STATUS_INVALID_INPUT = 2,
+  /**
+   * Loading a verifier, a prover or circuit assets failed.
+   */
+  ZKVM_STATUS_SETUP = 3,
+  /**
+   * The proof does not verify.
+   */
+  ZKVM_STATUS_INVALID_PROOF = 4,
+  /**
+   * Proving the task failed.
+   */
+  ZKVM_STATUS_PROVE = 5,
+  /**
+   * The library panicked.
+   */
+  ZKVM_STATUS_PANIC = 6,
+} ZkvmStatus;
+
+/**
+ * Prover of a circuit, see [`zkvm_prover_setup`].
+ */
+typedef struct ZkvmProver ZkvmProver;
+
+/**
+ * Verifier of the proofs of a release, see [`zkvm_verifier_setup`].
+ */
+typedef struct ZkvmVerifier ZkvmVerifier;
+
+#ifdef __cplusplus
+extern "C" {
+#endif  // __cplusplus
+
+/**
+ * The git version of the library, e.g. `v0.9.0-3-gabc1234`. The string is static, and must not
+ * be released.
+ */
+const char *zkvm_version(void);
+
+/**
+ * Release a string handed out by the library. Null is ignored.
+ *
+ * # Safety
+ *
+ * `s` must be null or a string handed out by the library, not released yet.
+ */
+void zkvm_string_free(char *s);
+
+/**
+ * Load the verifier of a release from its `verifier` directory, see
+ * [`UniversalVerifier::setup`]. The verifier is released with [`zkvm_verifier_free`].
+ *
+ * # Safety
+ *
+ * `dir_verifier` must be a NUL-terminated string, `out` valid for writes and `err_out` null or
+ * valid for writes.
+ */
+ZkvmStatus zkvm_verifier_setup(const char *dir_verifier, ZkvmVerifier **out, char **err_out);
+
+/**
+ * Release a verifier. Null is ignored.
+ *
+ * # Safety
+ *
+ * `verifier` must be null or a verifier handed out by [`zkvm_verifier_setup`], not released
+ * yet.
+ */
+void zkvm_verifier_free(ZkvmVerifier *verifier);
+
+/**
+ * Verify a STARK proof of the circuit of app vk `vk`, strictly, see
+ * [`UniversalVerifier::verify_stark_proof_strict`]. The deferral hook commit of the circuit is
+ * the one loaded by [`zkvm_verifier_setup`].
+ *
+ * # Safety
+ *
+ * `verifier` must be a live verifier, `proof` and `vk` NUL-terminated strings, and `err_out`
+ * null or valid for writes.
+ */
+ZkvmStatus zkvm_verify_stark_proof(const ZkvmVerifier *verifier,
+                                   const char *proof,
+                                   const char *vk,
+                                   char **err_out);
+
+/**
+ * Verify an EVM proof of the circuit of app vk `vk`, see
+ * [`UniversalVerifier::verify_evm_proof`]. The gas used is written to `gas` unless null.
+ *
+ * # Safety
+ *
+ * `verifier` must be a live verifier, `proof` and `vk` NUL-terminated strings, and `gas` and
+ * `err_out` null or valid for writes.
+ */
+ZkvmStatus zkvm_verify_evm_proof(const ZkvmVerifier *verifier,
+                                 const char *proof,
+                                 const char *vk,
+                                 uint64_t *gas,
+                                 char **err_out);
+
+/**
+ * Load the prover of a circuit. The prover is released with [`zkvm_prover_free`].
+ *
+ * `config` is a JSON object with the fields of a circuit of the daemon's config, i.e. `name`,
+ * `dir` and, for aggregation circuits, `child_dir`, and optionally `release`, `forks`,
+ * `dir_cache`, `dir_keys` and `dir_crash`, see [`ProverConfig`][scroll_zkvm_prover::ProverConfig].
+ *
+ * # Safety
+ *
+ * `config` must be a NUL-terminated string, `out` valid for writes and `err_out` null or valid
+ * for writes.
+ */
+ZkvmStatus zkvm_prover_setup(const char *config, ZkvmProver **out, char **err_out);
+
+/**
+ * Release a prover. Null is ignored.
+ *
+ * # Safety
+ *
+ * `prover` must be null or a prover handed out by [`zkvm_prover_setup`], not released yet.
+ */
+void zkvm_prover_free(ZkvmProver *prover);
+
+/**
+ * Prove a universal task, writing the proof as JSON to `out`, see
+ * [`Prover::gen_proof_universal`]. Tasks carrying child proofs are proven through deferral,
+ * see [`Prover::prove_aggregation`]. `with_snark` generates an EVM proof instead of a STARK
+ * proof.
+ *
+ * # Safety
+ *
+ * `prover` must be a live prover, `task` a NUL-terminated string, `out` valid for writes and
+ * `err_out` null or valid for writes.
+ */
+ZkvmStatus zkvm_prover_gen_proof(const ZkvmProver *prover,
+                                 const char *task,
+                                 bool with_snark,
+                                 char **out,
+                                 char **err_out);
+
+/**
+ * Write the vks of a prover as JSON to `out`, i.e. `{"app_vk": "<hex>"}`, with the `evm_vk` too
+ * if `with_evm`, which runs the keygen of the whole circuit. See [`Prover::get_app_vk`] and
+ * [`Prover::get_evm_vk`].
+ *
+ * # Safety
+ *
+ * `prover` must be a live prover, `out` valid for writes and `err_out` null or valid for
+ * writes.
+ */
+ZkvmStatus zkvm_prover_vks(const ZkvmProver *prover, bool with_evm, char **out, char **err_out);
+
+/**
+ * Write the app vk of the circuit in `dir_circuit` as JSON to `out`, i.e.
+ * `{"app_vk": "<hex>"}`, computed from its `app.vmexe` and `openvm.toml` without loading a
+ * prover, see [`compute_app_vk`].
+ *
+ * # Safety
+ *
+ * `dir_circuit` must be a NUL-terminated string, `out` valid for writes and `err_out` null or
+ * valid for writes.
+ */
+ZkvmStatus zkvm_circuit_vk(const char *dir_circuit, char **out, char **err_out);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SCROLL_ZKVM_H */
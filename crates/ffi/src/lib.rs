@@ -0,0 +1,475 @@
+//! C ABI over the [`UniversalVerifier`] and the [`Prover`], for the coordinator and other
+//! non-Rust hosts. The header is generated at build time, and checked against the committed
+//! `include/scroll_zkvm.h` by the `header` test.
+//!
+//! Conventions of the exported functions:
+//!
+//! - They return a [`ZkvmStatus`]. On failure, the error message is written to their last
+//!   argument `err_out` unless null, as a string to release with [`zkvm_string_free`]. The
+//!   message is not kept in thread-local state, so callers whose threads may change between
+//!   calls, e.g. goroutines, need no pinning.
+//! - Strings passed in are NUL-terminated UTF-8. Proofs, tasks and configs are JSON, in the same
+//!   format as the rest of the repo, e.g. the proofs written by the `zkvm` tool. VKs are hex.
+//! - Strings and handles handed out are owned by the caller, and released with
+//!   [`zkvm_string_free`], [`zkvm_verifier_free`] and [`zkvm_prover_free`].
+//! - Panics are caught at the boundary, and reported as [`ZkvmStatus::Panic`].
+
+use std::{
+    ffi::{CStr, CString, c_char},
+    fmt::Display,
+    panic::{AssertUnwindSafe, catch_unwind},
+    path::{Path, PathBuf},
+    ptr,
+};
+
+use scroll_zkvm_prover::{
+    Prover,
+    circuits::{CircuitConfig, load_circuits},
+    setup::compute_app_vk,
+    utils::GIT_VERSION,
+};
+use scroll_zkvm_types::{ProvingTask, proof::ProofEnum};
+use scroll_zkvm_verifier::verifier::UniversalVerifier;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+/// Outcome of an exported function.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZkvmStatus {
+    /// Success.
+    Ok = 0,
+    /// A required pointer was null.
+    NullPointer = 1,
+    /// An input is not valid UTF-8, JSON or hex, or not what the function expects.
+    InvalidInput = 2,
+    /// Loading a verifier, a prover or circuit assets failed.
+    Setup = 3,
+    /// The proof does not verify.
+    InvalidProof = 4,
+    /// Proving the task failed.
+    Prove = 5,
+    /// The library panicked.
+    Panic = 6,
+}
+
+/// Verifier of the proofs of a release, see [`zkvm_verifier_setup`].
+pub struct ZkvmVerifier(UniversalVerifier);
+
+/// Prover of a circuit, see [`zkvm_prover_setup`].
+pub struct ZkvmProver(Prover);
+
+/// Configuration of [`zkvm_prover_setup`]: the circuit, as in the `[[circuits]]` of the daemon's
+/// config, and where to keep the prover's files.
+#[derive(Deserialize)]
+struct ProverSetup {
+    #[serde(flatten)]
+    circuit: CircuitConfig,
+    /// Name of the guest release of the circuit, reported in the stats of its proofs.
+    #[serde(default)]
+    release: Option<String>,
+    /// Forks the prover admits. Empty to accept any fork.
+    #[serde(default)]
+    forks: Vec<String>,
+    #[serde(default)]
+    dir_cache: Option<PathBuf>,
+    #[serde(default)]
+    dir_keys: Option<PathBuf>,
+    #[serde(default)]
+    dir_crash: Option<PathBuf>,
+}
+
+/// Output of [`zkvm_prover_vks`] and [`zkvm_circuit_vk`].
+#[derive(Serialize)]
+struct Vks {
+    /// Hex-encoded app vk.
+    app_vk: String,
+    /// Hex-encoded EVM vk, if requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    evm_vk: Option<String>,
+}
+
+/// Error of an exported function: the status to return and the message to keep.
+struct FfiError {
+    status: ZkvmStatus,
+    message: String,
+}
+
+impl FfiError {
+    fn new(status: ZkvmStatus, message: impl Display) -> Self {
+        Self {
+            status,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Run `f` as the body of an exported function, writing its error or panic to `err_out`, which
+/// the exported functions require to be null or valid for writes.
+fn ffi_call(err_out: *mut *mut c_char, f: impl FnOnce() -> Result<(), FfiError>) -> ZkvmStatus {
+    if !err_out.is_null() {
+        // SAFETY: non-null `err_out` is valid for writes, see above.
+        unsafe { *err_out = ptr::null_mut() };
+    }
+    let (status, message) = match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => return ZkvmStatus::Ok,
+        Ok(Err(e)) => (e.status, e.message),
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            (ZkvmStatus::Panic, format!("panicked: {message}"))
+        }
+    };
+    if !err_out.is_null() {
+        let message = CString::new(message.replace('\0', " ")).expect("NUL bytes replaced");
+        // SAFETY: as above.
+        unsafe { *err_out = message.into_raw() };
+    }
+    status
+}
+
+/// Error of a required pointer `what` being null.
+fn null_pointer(what: &str) -> FfiError {
+    FfiError::new(ZkvmStatus::NullPointer, format!("{what} is null"))
+}
+
+/// The string `ptr` points to.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to a NUL-terminated string valid for `'a`.
+unsafe fn read_str<'a>(ptr: *const c_char, what: &str) -> Result<&'a str, FfiError> {
+    if ptr.is_null() {
+        return Err(null_pointer(what));
+    }
+    unsafe { CStr::from_ptr(ptr) }.to_str().map_err(|e| {
+        FfiError::new(
+            ZkvmStatus::InvalidInput,
+            format!("{what} is not UTF-8: {e}"),
+        )
+    })
+}
+
+/// Deserialize the JSON string `ptr` points to. Proofs nest deeply, so the recursion limit of
+/// serde_json is lifted, as in [`read_json_deep`][scroll_zkvm_prover::utils::read_json_deep].
+///
+/// # Safety
+///
+/// Same as [`read_str`].
+unsafe fn read_json<T: DeserializeOwned>(ptr: *const c_char, what: &str) -> Result<T, FfiError> {
+    let json = unsafe { read_str(ptr, what) }?;
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    deserializer.disable_recursion_limit();
+    let deserializer = serde_stacker::Deserializer::new(&mut deserializer);
+    T::deserialize(deserializer).map_err(|e| {
+        FfiError::new(
+            ZkvmStatus::InvalidInput,
+            format!("{what} is not valid JSON: {e}"),
+        )
+    })
+}
+
+/// Decode the hex string `ptr` points to, with or without `0x` prefix.
+///
+/// # Safety
+///
+/// Same as [`read_str`].
+unsafe fn read_hex(ptr: *const c_char, what: &str) -> Result<Vec<u8>, FfiError> {
+    let s = unsafe { read_str(ptr, what) }?;
+    hex::decode(s.trim_start_matches("0x"))
+        .map_err(|e| FfiError::new(ZkvmStatus::InvalidInput, format!("{what} is not hex: {e}")))
+}
+
+/// Hand `value` out through `out`, as a string to release with [`zkvm_string_free`].
+///
+/// # Safety
+///
+/// `out` must be null or valid for writes.
+unsafe fn write_json(out: *mut *mut c_char, value: &impl Serialize) -> Result<(), FfiError> {
+    if out.is_null() {
+        return Err(null_pointer("out"));
+    }
+    let json =
+        serde_json::to_string(value).map_err(|e| FfiError::new(ZkvmStatus::InvalidInput, e))?;
+    let json = CString::new(json).expect("JSON has no NUL bytes");
+    unsafe { *out = json.into_raw() };
+    Ok(())
+}
+
+/// Hand `handle` out through `out`, as a pointer to release with the matching free function.
+///
+/// # Safety
+///
+/// `out` must be null or valid for writes.
+unsafe fn write_handle<T>(out: *mut *mut T, handle: T) -> Result<(), FfiError> {
+    if out.is_null() {
+        return Err(null_pointer("out"));
+    }
+    unsafe { *out = Box::into_raw(Box::new(handle)) };
+    Ok(())
+}
+
+/// The handle `ptr` points to.
+///
+/// # Safety
+///
+/// `ptr` must be null or a live handle handed out by this library.
+unsafe fn read_handle<'a, T>(ptr: *const T, what: &str) -> Result<&'a T, FfiError> {
+    unsafe { ptr.as_ref() }.ok_or_else(|| null_pointer(what))
+}
+
+/// The git version of the library, e.g. `v0.9.0-3-gabc1234`. The string is static, and must not
+/// be released.
+#[no_mangle]
+pub extern "C" fn zkvm_version() -> *const c_char {
+    static VERSION: std::sync::OnceLock<CString> = std::sync::OnceLock::new();
+    VERSION
+        .get_or_init(|| CString::new(GIT_VERSION).expect("version has no NUL bytes"))
+        .as_ptr()
+}
+
+/// Release a string handed out by the library. Null is ignored.
+///
+/// # Safety
+///
+/// `s` must be null or a string handed out by the library, not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn zkvm_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+/// Load the verifier of a release from its `verifier` directory, see
+/// [`UniversalVerifier::setup`]. The verifier is released with [`zkvm_verifier_free`].
+///
+/// # Safety
+///
+/// `dir_verifier` must be a NUL-terminated string, `out` valid for writes and `err_out` null or
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn zkvm_verifier_setup(
+    dir_verifier: *const c_char,
+    out: *mut *mut ZkvmVerifier,
+    err_out: *mut *mut c_char,
+) -> ZkvmStatus {
+    ffi_call(err_out, || {
+        let dir_verifier = unsafe { read_str(dir_verifier, "dir_verifier") }?;
+        let verifier = UniversalVerifier::setup(dir_verifier)
+            .map_err(|e| FfiError::new(ZkvmStatus::Setup, format!("{e:#}")))?;
+        unsafe { write_handle(out, ZkvmVerifier(verifier)) }
+    })
+}
+
+/// Release a verifier. Null is ignored.
+///
+/// # Safety
+///
+/// `verifier` must be null or a verifier handed out by [`zkvm_verifier_setup`], not released
+/// yet.
+#[no_mangle]
+pub unsafe extern "C" fn zkvm_verifier_free(verifier: *mut ZkvmVerifier) {
+    if !verifier.is_null() {
+        drop(unsafe { Box::from_raw(verifier) });
+    }
+}
+
+/// Verify a STARK proof of the circuit of app vk `vk`, strictly, see
+/// [`UniversalVerifier::verify_stark_proof_strict`]. The deferral hook commit of the circuit is
+/// the one loaded by [`zkvm_verifier_setup`].
+///
+/// # Safety
+///
+/// `verifier` must be a live verifier, `proof` and `vk` NUL-terminated strings, and `err_out`
+/// null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn zkvm_verify_stark_proof(
+    verifier: *const ZkvmVerifier,
+    proof: *const c_char,
+    vk: *const c_char,
+    err_out: *mut *mut c_char,
+) -> ZkvmStatus {
+    ffi_call(err_out, || {
+        let verifier = unsafe { read_handle(verifier, "verifier") }?;
+        let ProofEnum::Stark(proof) = (unsafe { read_json(proof, "proof") })? else {
+            return Err(FfiError::new(
+                ZkvmStatus::InvalidInput,
+                "proof is not a STARK proof",
+            ));
+        };
+        let vk = unsafe { read_hex(vk, "vk") }?;
+        verifier
+            .0
+            .verify_stark_proof_strict(&proof, &vk)
+            .map_err(|e| FfiError::new(ZkvmStatus::InvalidProof, format!("{e:#}")))
+    })
+}
+
+/// Verify an EVM proof of the circuit of app vk `vk`, see
+/// [`UniversalVerifier::verify_evm_proof`]. The gas used is written to `gas` unless null.
+///
+/// # Safety
+///
+/// `verifier` must be a live verifier, `proof` and `vk` NUL-terminated strings, and `gas` and
+/// `err_out` null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn zkvm_verify_evm_proof(
+    verifier: *const ZkvmVerifier,
+    proof: *const c_char,
+    vk: *const c_char,
+    gas: *mut u64,
+    err_out: *mut *mut c_char,
+) -> ZkvmStatus {
+    ffi_call(err_out, || {
+        let verifier = unsafe { read_handle(verifier, "verifier") }?;
+        let ProofEnum::Evm(proof) = (unsafe { read_json(proof, "proof") })? else {
+            return Err(FfiError::new(
+                ZkvmStatus::InvalidInput,
+                "proof is not an EVM proof",
+            ));
+        };
+        let vk = unsafe { read_hex(vk, "vk") }?;
+        let gas_used = verifier
+            .0
+            .verify_evm_proof(&proof.into(), &vk)
+            .map_err(|e| FfiError::new(ZkvmStatus::InvalidProof, format!("{e:#}")))?;
+        if !gas.is_null() {
+            unsafe { *gas = gas_used };
+        }
+        Ok(())
+    })
+}
+
+/// Load the prover of a circuit. The prover is released with [`zkvm_prover_free`].
+///
+/// `config` is a JSON object with the fields of a circuit of the daemon's config, i.e. `name`,
+/// `dir` and, for aggregation circuits, `child_dir`, and optionally `release`, `forks`,
+/// `dir_cache`, `dir_keys` and `dir_crash`, see [`ProverConfig`][scroll_zkvm_prover::ProverConfig].
+///
+/// # Safety
+///
+/// `config` must be a NUL-terminated string, `out` valid for writes and `err_out` null or valid
+/// for writes.
+#[no_mangle]
+pub unsafe extern "C" fn zkvm_prover_setup(
+    config: *const c_char,
+    out: *mut *mut ZkvmProver,
+    err_out: *mut *mut c_char,
+) -> ZkvmStatus {
+    ffi_call(err_out, || {
+        let setup: ProverSetup = unsafe { read_json(config, "config") }?;
+        let mut circuits = load_circuits(
+            &[setup.circuit],
+            setup.release.as_deref(),
+            &setup.forks,
+            setup.dir_cache.as_deref(),
+            setup.dir_keys.as_deref(),
+            setup.dir_crash.as_deref(),
+        )
+        .map_err(|e| FfiError::new(ZkvmStatus::Setup, e))?;
+        let circuit = circuits.pop().expect("one circuit loaded");
+        unsafe { write_handle(out, ZkvmProver(circuit.prover)) }
+    })
+}
+
+/// Release a prover. Null is ignored.
+///
+/// # Safety
+///
+/// `prover` must be null or a prover handed out by [`zkvm_prover_setup`], not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn zkvm_prover_free(prover: *mut ZkvmProver) {
+    if !prover.is_null() {
+        drop(unsafe { Box::from_raw(prover) });
+    }
+}
+
+/// Prove a universal task, writing the proof as JSON to `out`, see
+/// [`Prover::gen_proof_universal`]. Tasks carrying child proofs are proven through deferral,
+/// see [`Prover::prove_aggregation`]. `with_snark` generates an EVM proof instead of a STARK
+/// proof.
+///
+/// # Safety
+///
+/// `prover` must be a live prover, `task` a NUL-terminated string, `out` valid for writes and
+/// `err_out` null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn zkvm_prover_gen_proof(
+    prover: *const ZkvmProver,
+    task: *const c_char,
+    with_snark: bool,
+    out: *mut *mut c_char,
+    err_out: *mut *mut c_char,
+) -> ZkvmStatus {
+    ffi_call(err_out, || {
+        let prover = &unsafe { read_handle(prover, "prover") }?.0;
+        let task: ProvingTask = unsafe { read_json(task, "task") }?;
+        let proof = if task.aggregated_proofs.is_empty() {
+            prover.gen_proof_universal(&task, with_snark)
+        } else {
+            prover.prove_aggregation(&task, &task.aggregated_proofs, with_snark)
+        }
+        .map_err(|e| FfiError::new(ZkvmStatus::Prove, e))?;
+        unsafe { write_json(out, &proof) }
+    })
+}
+
+/// Write the vks of a prover as JSON to `out`, i.e. `{"app_vk": "<hex>"}`, with the `evm_vk` too
+/// if `with_evm`, which runs the keygen of the whole circuit. See [`Prover::get_app_vk`] and
+/// [`Prover::get_evm_vk`].
+///
+/// # Safety
+///
+/// `prover` must be a live prover, `out` valid for writes and `err_out` null or valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn zkvm_prover_vks(
+    prover: *const ZkvmProver,
+    with_evm: bool,
+    out: *mut *mut c_char,
+    err_out: *mut *mut c_char,
+) -> ZkvmStatus {
+    ffi_call(err_out, || {
+        let prover = &unsafe { read_handle(prover, "prover") }?.0;
+        let evm_vk = with_evm
+            .then(|| prover.get_evm_vk())
+            .transpose()
+            .map_err(|e| FfiError::new(ZkvmStatus::Setup, e))?;
+        let vks = Vks {
+            app_vk: hex::encode(prover.get_app_vk()),
+            evm_vk: evm_vk.map(hex::encode),
+        };
+        unsafe { write_json(out, &vks) }
+    })
+}
+
+/// Write the app vk of the circuit in `dir_circuit` as JSON to `out`, i.e.
+/// `{"app_vk": "<hex>"}`, computed from its `app.vmexe` and `openvm.toml` without loading a
+/// prover, see [`compute_app_vk`].
+///
+/// # Safety
+///
+/// `dir_circuit` must be a NUL-terminated string, `out` valid for writes and `err_out` null or
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn zkvm_circuit_vk(
+    dir_circuit: *const c_char,
+    out: *mut *mut c_char,
+    err_out: *mut *mut c_char,
+) -> ZkvmStatus {
+    ffi_call(err_out, || {
+        let dir_circuit = Path::new(unsafe { read_str(dir_circuit, "dir_circuit") }?);
+        let app_vk = compute_app_vk(
+            dir_circuit.join("app.vmexe"),
+            dir_circuit.join("openvm.toml"),
+        )
+        .map_err(|e| FfiError::new(ZkvmStatus::Setup, e))?;
+        let vks = Vks {
+            app_vk: hex::encode(app_vk),
+            evm_vk: None,
+        };
+        unsafe { write_json(out, &vks) }
+    })
+}
@@ -0,0 +1,138 @@
+/*
+ * Exercises the C ABI of libscroll_zkvm, as the coordinator would.
+ *
+ * Without arguments, only the checks not needing release assets run: null pointers, invalid
+ * inputs, error reporting and freeing. With arguments, a proof is verified too:
+ *
+ *   harness <verifier dir> <proof.json> <vk hex>
+ *
+ * Exits with 0 if all checks pass.
+ */
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "scroll_zkvm.h"
+
+static int failures = 0;
+
+#define CHECK(cond)                                                   \
+  do {                                                                \
+    if (!(cond)) {                                                    \
+      fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, \
+              #cond);                                                 \
+      failures++;                                                     \
+    }                                                                 \
+  } while (0)
+
+static char *read_file(const char *path) {
+  FILE *f = fopen(path, "rb");
+  if (f == NULL) {
+    fprintf(stderr, "failed to open %s\n", path);
+    exit(2);
+  }
+  fseek(f, 0, SEEK_END);
+  long len = ftell(f);
+  fseek(f, 0, SEEK_SET);
+  char *buf = malloc(len + 1);
+  if (buf == NULL || fread(buf, 1, len, f) != (size_t)len) {
+    fprintf(stderr, "failed to read %s\n", path);
+    exit(2);
+  }
+  buf[len] = '\0';
+  fclose(f);
+  return buf;
+}
+
+static void check_without_assets(void) {
+  const char *version = zkvm_version();
+  CHECK(version != NULL && strlen(version) > 0);
+
+  char *err = NULL;
+  ZkvmVerifier *verifier = NULL;
+  CHECK(zkvm_verifier_setup(NULL, &verifier, &err) == ZKVM_STATUS_NULL_POINTER);
+  CHECK(verifier == NULL);
+  CHECK(err != NULL);
+  zkvm_string_free(err);
+  CHECK(zkvm_verifier_setup("/nonexistent/verifier", &verifier, &err) == ZKVM_STATUS_SETUP);
+  CHECK(verifier == NULL);
+  CHECK(err != NULL && strlen(err) > 0);
+  zkvm_string_free(err);
+  /* The message is optional. */
+  CHECK(zkvm_verifier_setup("/nonexistent/verifier", &verifier, NULL) == ZKVM_STATUS_SETUP);
+
+  CHECK(zkvm_verify_stark_proof(NULL, "{}", "00", NULL) == ZKVM_STATUS_NULL_POINTER);
+  CHECK(zkvm_verify_evm_proof(NULL, "{}", "00", NULL, NULL) == ZKVM_STATUS_NULL_POINTER);
+
+  ZkvmProver *prover = NULL;
+  CHECK(zkvm_prover_setup("not json", &prover, NULL) == ZKVM_STATUS_INVALID_INPUT);
+  CHECK(zkvm_prover_setup("{\"name\": \"chunk\"}", &prover, NULL) ==
+        ZKVM_STATUS_INVALID_INPUT);
+  CHECK(zkvm_prover_setup("{\"name\": \"chunk\", \"dir\": \"/nonexistent/chunk\"}", &prover,
+                          NULL) == ZKVM_STATUS_SETUP);
+  CHECK(prover == NULL);
+
+  char *out = NULL;
+  CHECK(zkvm_prover_gen_proof(NULL, "{}", false, &out, NULL) == ZKVM_STATUS_NULL_POINTER);
+  CHECK(zkvm_prover_vks(NULL, false, &out, NULL) == ZKVM_STATUS_NULL_POINTER);
+  CHECK(zkvm_circuit_vk("/nonexistent/chunk", &out, NULL) == ZKVM_STATUS_SETUP);
+  CHECK(out == NULL);
+
+  /* Releasing null is a no-op. */
+  zkvm_string_free(NULL);
+  zkvm_verifier_free(NULL);
+  zkvm_prover_free(NULL);
+}
+
+static void check_proof(const char *dir_verifier, const char *path_proof, const char *vk) {
+  char *err = NULL;
+  ZkvmVerifier *verifier = NULL;
+  ZkvmStatus status = zkvm_verifier_setup(dir_verifier, &verifier, &err);
+  if (status != ZKVM_STATUS_OK) {
+    fprintf(stderr, "setup failed (%d): %s\n", status, err);
+    zkvm_string_free(err);
+    failures++;
+    return;
+  }
+
+  char *proof = read_file(path_proof);
+
+  /* A proof is either a STARK or an EVM proof, the other kind is rejected as invalid input. */
+  status = zkvm_verify_stark_proof(verifier, proof, vk, NULL);
+  if (status == ZKVM_STATUS_INVALID_INPUT) {
+    uint64_t gas = 0;
+    status = zkvm_verify_evm_proof(verifier, proof, vk, &gas, &err);
+    CHECK(status != ZKVM_STATUS_OK || gas > 0);
+  } else if (status != ZKVM_STATUS_OK) {
+    /* Get the message of the failure. */
+    zkvm_verify_stark_proof(verifier, proof, vk, &err);
+  }
+  if (status != ZKVM_STATUS_OK) {
+    fprintf(stderr, "verification failed (%d): %s\n", status, err);
+    failures++;
+  }
+  zkvm_string_free(err);
+
+  /* The proof must not verify against another vk. */
+  CHECK(zkvm_verify_stark_proof(verifier, proof, "00", NULL) != ZKVM_STATUS_OK);
+  CHECK(zkvm_verify_stark_proof(verifier, proof, "not hex", NULL) == ZKVM_STATUS_INVALID_INPUT);
+
+  free(proof);
+  zkvm_verifier_free(verifier);
+}
+
+int main(int argc, char **argv) {
+  printf("libscroll_zkvm %s\n", zkvm_version());
+
+  check_without_assets();
+  if (argc >= 4) {
+    check_proof(argv[1], argv[2], argv[3]);
+  }
+
+  if (failures > 0) {
+    fprintf(stderr, "%d check(s) failed\n", failures);
+    return 1;
+  }
+  printf("all checks passed\n");
+  return 0;
+}
@@ -0,0 +1,54 @@
+//! Compile `tests/c/harness.c` against the header and the cdylib of this build, and run it.
+//!
+//! The harness verifies a proof too if `ZKVM_FFI_VERIFIER`, `ZKVM_FFI_PROOF` and `ZKVM_FFI_VK`
+//! are set, see the harness.
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Directories the cdylib of this build may be in, i.e. `target/<profile>/deps` where the test
+/// binary lives, and `target/<profile>` where cargo copies it to.
+fn lib_dirs() -> Vec<PathBuf> {
+    let exe = std::env::current_exe().expect("path of the test binary");
+    exe.ancestors()
+        .skip(1)
+        .take(2)
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+#[test]
+fn c_harness() {
+    let dir_crate = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let dir_out = Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let path_harness = dir_out.join("harness");
+
+    let mut cc = Command::new(std::env::var("CC").unwrap_or_else(|_| "cc".to_string()));
+    cc.arg(dir_crate.join("tests").join("c").join("harness.c"))
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(env!("OUT_DIR"))
+        .arg("-o")
+        .arg(&path_harness);
+    for dir in lib_dirs() {
+        cc.arg("-L")
+            .arg(&dir)
+            .arg(format!("-Wl,-rpath,{}", dir.display()));
+    }
+    let status = cc
+        .arg("-lscroll_zkvm")
+        .status()
+        .expect("failed to run the C compiler");
+    assert!(status.success(), "failed to compile the C harness");
+
+    let mut harness = Command::new(&path_harness);
+    let assets = ["ZKVM_FFI_VERIFIER", "ZKVM_FFI_PROOF", "ZKVM_FFI_VK"].map(std::env::var);
+    if let [Ok(verifier), Ok(proof), Ok(vk)] = assets {
+        harness.args([verifier, proof, vk]);
+    }
+    let status = harness.status().expect("failed to run the C harness");
+    assert!(status.success(), "C harness failed");
+}
@@ -0,0 +1,28 @@
+//! Check that the committed `include/scroll_zkvm.h` is the header generated from this build.
+//!
+//! Run with `UPDATE_FFI_HEADER=1` to write the generated header to `include/scroll_zkvm.h`,
+//! see `make ffi-header`.
+
+use std::path::Path;
+
+#[test]
+fn header() {
+    let path_generated = Path::new(env!("OUT_DIR")).join("scroll_zkvm.h");
+    let path_committed = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("include")
+        .join("scroll_zkvm.h");
+    let generated = std::fs::read_to_string(&path_generated)
+        .unwrap_or_else(|e| panic!("failed to read {}: {e}", path_generated.display()));
+
+    if std::env::var_os("UPDATE_FFI_HEADER").is_some() {
+        std::fs::write(&path_committed, &generated)
+            .unwrap_or_else(|e| panic!("failed to write {}: {e}", path_committed.display()));
+        return;
+    }
+    let committed = std::fs::read_to_string(&path_committed).unwrap_or_default();
+    assert!(
+        committed == generated,
+        "{} is out of date, regenerate it with `make ffi-header`",
+        path_committed.display()
+    );
+}
@@ -0,0 +1,206 @@
+//! In-process mock of a coordinator, to test the pull loop of
+//! [`Client`][scroll_zkvm_prover::coordinator::Client] offline.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Mutex,
+};
+
+use scroll_zkvm_prover::coordinator::{
+    AssignedTask, Coordinator, CoordinatorError, LoginRequest, Submission, TaskRequest,
+};
+use scroll_zkvm_types::proof::{ProofEnum, StarkProof};
+
+use crate::{
+    ProverTester,
+    testers::chunk::{ChunkProverTester, ChunkTaskGenerator},
+};
+
+#[derive(Default)]
+struct MockState {
+    next_token: u64,
+    tokens: HashSet<String>,
+    pending: VecDeque<AssignedTask>,
+    assigned: HashMap<String, AssignedTask>,
+    revoked: HashSet<String>,
+    heartbeats: HashMap<String, u64>,
+    proofs: HashMap<String, ProofEnum>,
+    failures: HashMap<String, String>,
+}
+
+/// Coordinator serving a fixed list of tasks and recording the submissions.
+#[derive(Default)]
+pub struct MockCoordinator {
+    state: Mutex<MockState>,
+}
+
+impl MockCoordinator {
+    /// Coordinator without any task.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Coordinator serving the chunk tasks built from the block witnesses in the testdata.
+    pub fn from_chunk_fixtures(
+        generators: &mut [ChunkTaskGenerator],
+        vk: &[u8],
+    ) -> eyre::Result<Self> {
+        let coordinator = Self::new();
+        for generator in generators {
+            let witness = generator.get_or_build_witness()?;
+            let mut task =
+                ChunkProverTester::build_universal_task(&witness, std::iter::empty(), vec![])?;
+            task.vk = vk.to_vec();
+            coordinator.push_task(AssignedTask {
+                task_id: task.identifier.clone(),
+                circuit: ChunkProverTester::NAME.to_string(),
+                with_snark: false,
+                child_task_ids: vec![],
+                task,
+            });
+        }
+        Ok(coordinator)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().expect("mock coordinator poisoned")
+    }
+
+    /// Queue a task to be assigned.
+    pub fn push_task(&self, task: AssignedTask) {
+        self.lock().pending.push_back(task);
+    }
+
+    /// Submit a proof on behalf of a task, so that it can be fetched as a child proof.
+    pub fn insert_proof(&self, task_id: &str, proof: ProofEnum) {
+        self.lock().proofs.insert(task_id.to_string(), proof);
+    }
+
+    /// Invalidate all session tokens, forcing the clients to log in again.
+    pub fn expire_tokens(&self) {
+        self.lock().tokens.clear();
+    }
+
+    /// Take the task away from its prover; the next heartbeat reports it as not assigned.
+    pub fn revoke(&self, task_id: &str) {
+        self.lock().revoked.insert(task_id.to_string());
+    }
+
+    /// The proof submitted for the task.
+    pub fn proof(&self, task_id: &str) -> Option<ProofEnum> {
+        self.lock().proofs.get(task_id).cloned()
+    }
+
+    /// The failure submitted for the task.
+    pub fn failure(&self, task_id: &str) -> Option<String> {
+        self.lock().failures.get(task_id).cloned()
+    }
+
+    /// Number of heartbeats received for the task.
+    pub fn heartbeats(&self, task_id: &str) -> u64 {
+        self.lock()
+            .heartbeats
+            .get(task_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Number of tasks not assigned yet.
+    pub fn num_pending(&self) -> usize {
+        self.lock().pending.len()
+    }
+
+    fn authorize(&self, token: &str) -> Result<(), CoordinatorError> {
+        if self.lock().tokens.contains(token) {
+            Ok(())
+        } else {
+            Err(CoordinatorError::Unauthorized(format!(
+                "unknown token {token}"
+            )))
+        }
+    }
+}
+
+impl Coordinator for MockCoordinator {
+    fn login(&self, request: &LoginRequest) -> Result<String, CoordinatorError> {
+        let mut state = self.lock();
+        let token = format!("{}-{}", request.prover_name, state.next_token);
+        state.next_token += 1;
+        state.tokens.insert(token.clone());
+        Ok(token)
+    }
+
+    fn get_task(
+        &self,
+        token: &str,
+        request: &TaskRequest,
+    ) -> Result<Option<AssignedTask>, CoordinatorError> {
+        self.authorize(token)?;
+        let mut state = self.lock();
+        let Some(idx) = state
+            .pending
+            .iter()
+            .position(|task| request.circuits.contains(&task.circuit))
+        else {
+            return Ok(None);
+        };
+        let task = state.pending.remove(idx).expect("index is in range");
+        state.assigned.insert(task.task_id.clone(), task.clone());
+        Ok(Some(task))
+    }
+
+    fn get_proofs(
+        &self,
+        token: &str,
+        task_ids: &[String],
+    ) -> Result<Vec<StarkProof>, CoordinatorError> {
+        self.authorize(token)?;
+        let state = self.lock();
+        task_ids
+            .iter()
+            .map(|task_id| {
+                state
+                    .proofs
+                    .get(task_id)
+                    .and_then(|proof| proof.as_stark_proof())
+                    .cloned()
+                    .ok_or_else(|| CoordinatorError::Api {
+                        code: 404,
+                        message: format!("no stark proof for task {task_id}"),
+                    })
+            })
+            .collect()
+    }
+
+    fn heartbeat(&self, token: &str, task_id: &str) -> Result<bool, CoordinatorError> {
+        self.authorize(token)?;
+        let mut state = self.lock();
+        *state.heartbeats.entry(task_id.to_string()).or_default() += 1;
+        Ok(!state.revoked.contains(task_id))
+    }
+
+    fn submit(&self, token: &str, submission: &Submission) -> Result<(), CoordinatorError> {
+        self.authorize(token)?;
+        let mut state = self.lock();
+        if state.assigned.remove(&submission.task_id).is_none() {
+            return Err(CoordinatorError::Api {
+                code: 400,
+                message: format!("task {} is not assigned", submission.task_id),
+            });
+        }
+        match (&submission.proof, &submission.failure) {
+            (Some(proof), _) => {
+                state
+                    .proofs
+                    .insert(submission.task_id.clone(), proof.clone());
+            }
+            (None, failure) => {
+                state.failures.insert(
+                    submission.task_id.clone(),
+                    failure.clone().unwrap_or_default(),
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,13 +1,7 @@
 use cargo_metadata::MetadataCommand;
 use once_cell::sync::OnceCell;
-use openvm_circuit::arch::deferral::DeferralState;
-use openvm_sdk::{DeferralInput, Sdk, StdIn};
-use openvm_stark_sdk::openvm_stark_backend::codec::Decode;
-use openvm_verify_stark_circuit::extension::{get_deferral_state, get_raw_deferral_results};
-use openvm_verify_stark_host::{
-    VmStarkProof,
-    vk::{VerificationBaseline, VmStarkVerifyingKey},
-};
+use openvm_continuations::CommitBytes;
+use openvm_sdk::{Sdk, StdIn};
 use scroll_zkvm_prover::{
     Prover,
     setup::{read_app_config, read_app_exe},
@@ -23,7 +17,6 @@ use scroll_zkvm_types::{
 use scroll_zkvm_verifier::verifier::{AGG_STARK_PROVING_KEY, UniversalVerifier};
 use std::collections::HashMap;
 use std::{
-    io::Cursor,
     path::{Path, PathBuf},
     process,
     sync::LazyLock,
@@ -31,6 +24,8 @@ use std::{
 use tracing::instrument;
 use tracing_subscriber::{fmt::format::FmtSpan, layer::SubscriberExt, util::SubscriberInitExt};
 
+pub mod coordinator;
+
 pub mod testers;
 
 pub mod utils;
@@ -201,6 +196,12 @@ pub trait ProverTester {
         let config = scroll_zkvm_prover::ProverConfig {
             path_app_exe,
             path_app_config,
+            dir_cache: None,
+            dir_keys: None,
+            dir_crash: None,
+            release: None,
+            forks: vec![],
+            agg_config: None,
         };
         let prover = Prover::setup(config, Some(Self::NAME))?;
 
@@ -241,19 +242,17 @@ pub trait ProverTester {
 pub trait TaskProver {
     fn name(&self) -> &str;
     fn prove_task(&mut self, t: &UniversalProvingTask, gen_snark: bool) -> eyre::Result<ProofEnum>;
-    fn prove_task_with_deferral(
+    /// Prove an aggregation task, whose child proofs are its `aggregated_proofs`.
+    fn prove_aggregation_task(
         &mut self,
         t: &UniversalProvingTask,
         gen_snark: bool,
-        def_inputs: &[DeferralInput],
-        def_states: &[DeferralState],
     ) -> eyre::Result<ProofEnum> {
-        // Default: ignore deferral inputs for backward compatibility.
-        let _ = def_inputs;
-        let _ = def_states;
         self.prove_task(t, gen_snark)
     }
     fn get_vk(&mut self) -> eyre::Result<Vec<u8>>;
+    /// The deferral hook commit of the circuit, verified in the baseline of its proofs.
+    fn get_def_hook_commit(&self) -> eyre::Result<Option<CommitBytes>>;
     fn get_agg_vk(
         &self,
     ) -> eyre::Result<
@@ -261,8 +260,6 @@ pub trait TaskProver {
             openvm_sdk::SC,
         >,
     >;
-    /// Returns the cached commit of the verify-stark deferral circuit (def_idx 0).
-    fn deferral_cached_commit(&self) -> eyre::Result<openvm_continuations::CommitBytes>;
 }
 
 impl TaskProver for Prover {
@@ -282,22 +279,12 @@ impl TaskProver for Prover {
         }
     }
 
-    fn prove_task_with_deferral(
+    fn prove_aggregation_task(
         &mut self,
         t: &UniversalProvingTask,
         gen_snark: bool,
-        def_inputs: &[DeferralInput],
-        def_states: &[DeferralState],
     ) -> eyre::Result<ProofEnum> {
-        use scroll_zkvm_prover::task::ProvingTask;
-        let mut stdin = t.build_guest_input();
-        stdin.deferrals = def_states.to_vec();
-        if !gen_snark {
-            Ok(self.gen_proof_stark(stdin, def_inputs)?.into())
-        } else {
-            let proof: EvmProof = self.gen_proof_snark(stdin, def_inputs)?.into();
-            Ok(proof.into())
-        }
+        Ok(self.prove_aggregation(t, &t.aggregated_proofs, gen_snark)?)
     }
 
     fn get_vk(&mut self) -> eyre::Result<Vec<u8>> {
@@ -310,6 +297,10 @@ impl TaskProver for Prover {
         Ok(serialize_vk::serialize(commitment))
     }
 
+    fn get_def_hook_commit(&self) -> eyre::Result<Option<CommitBytes>> {
+        Ok(self.def_hook_commit()?)
+    }
+
     fn get_agg_vk(
         &self,
     ) -> eyre::Result<
@@ -319,19 +310,6 @@ impl TaskProver for Prover {
     > {
         Ok((*self.load_agg_vk()?).clone())
     }
-
-    fn deferral_cached_commit(&self) -> eyre::Result<openvm_continuations::CommitBytes> {
-        let mut commits = self
-            .sdk()?
-            .deferral_circuit_cached_commits(0)
-            .map_err(|e| eyre::eyre!("failed to get deferral cached commits: {e}"))?;
-        eyre::ensure!(
-            commits.len() == 1,
-            "expected one deferral circuit, got {}",
-            commits.len()
-        );
-        Ok(commits.pop().unwrap())
-    }
 }
 
 /// Enviroment settings for test: fork dir
@@ -415,104 +393,15 @@ pub fn tester_execute<T: ProverTester>(
         .agg_pk(AGG_STARK_PROVING_KEY.clone())
         .build()
         .map_err(|e| eyre::eyre!("sdk build failed: {e}"))?;
-    let ret = scroll_zkvm_prover::utils::vm::execute_guest(&sdk, app_exe, &stdin)?;
+    let ret = scroll_zkvm_prover::utils::vm::execute_guest(
+        &sdk,
+        app_exe,
+        &stdin,
+        &scroll_zkvm_prover::utils::vm::GuestOutput::default(),
+    )?;
     Ok(ret)
 }
 
-/// Decode a [`StarkProof`] into a [`VmStarkProof`] and its stored [`VerificationBaseline`].
-fn decode_stark_proof(proof: &StarkProof) -> eyre::Result<(VmStarkProof, VerificationBaseline)> {
-    use openvm_circuit::system::memory::merkle::public_values::UserPublicValuesProof;
-
-    let inner =
-        openvm_stark_sdk::openvm_stark_backend::proof::Proof::decode_from_bytes(&proof.proof)
-            .map_err(|e| eyre::eyre!("decode proof failed: {e}"))?;
-    let user_pvs_proof =
-        UserPublicValuesProof::decode::<openvm_sdk::SC, _>(&mut Cursor::new(&proof.user_pvs_proof))
-            .map_err(|e| eyre::eyre!("decode user_pvs_proof failed: {e}"))?;
-    let deferral_merkle_proofs = if proof.deferral_merkle_proofs.is_empty() {
-        None
-    } else {
-        Some(
-            openvm_verify_stark_host::deferral::DeferralMerkleProofs::decode(&mut Cursor::new(
-                &proof.deferral_merkle_proofs,
-            ))
-            .map_err(|e| eyre::eyre!("decode deferral_merkle_proofs failed: {e}"))?,
-        )
-    };
-    let baseline = if proof.baseline.is_empty() {
-        // Fallback for proofs without a stored baseline (should not happen in v2).
-        eyre::bail!("stark proof missing verification baseline");
-    } else {
-        serde_json::from_slice(&proof.baseline)
-            .map_err(|e| eyre::eyre!("decode baseline failed: {e}"))?
-    };
-    Ok((
-        VmStarkProof {
-            inner,
-            user_pvs_proof,
-            deferral_merkle_proofs,
-        },
-        baseline,
-    ))
-}
-
-/// Compute deferral inputs and states from child proofs.
-#[allow(clippy::type_complexity)]
-pub fn compute_deferral_data(
-    child_prover: &Prover,
-    cached_commit: openvm_continuations::CommitBytes,
-    proofs: &[&StarkProof],
-) -> eyre::Result<(Vec<[u8; 32]>, Vec<DeferralInput>, Vec<DeferralState>)> {
-    // Load the child agg VK from the pre-built asset when available; deriving it
-    // from the SDK constructs the child's (GPU) aggregation prover just for the VK.
-    let mvk = (*child_prover.load_agg_vk()?).clone();
-
-    let (vm_proofs, baselines): (Vec<VmStarkProof>, Vec<VerificationBaseline>) = proofs
-        .iter()
-        .map(|p| decode_stark_proof(p))
-        .collect::<eyre::Result<Vec<_>>>()?
-        .into_iter()
-        .unzip();
-
-    // All child proofs must share the same baseline (same app/config).
-    let baseline = baselines
-        .first()
-        .cloned()
-        .ok_or_else(|| eyre::eyre!("no child proofs to compute deferral data"))?;
-    for (i, b) in baselines.iter().enumerate().skip(1) {
-        if b.app_exe_commit != baseline.app_exe_commit || b.app_vk_commit != baseline.app_vk_commit
-        {
-            eyre::bail!(
-                "child proof {i} has a different verification baseline; all child proofs must use the same app exe/vk commitment"
-            );
-        }
-    }
-
-    let vk = VmStarkVerifyingKey { mvk, baseline };
-
-    let cached_commit: openvm_stark_sdk::config::baby_bear_poseidon2::Digest = cached_commit.into();
-
-    let raw_results = get_raw_deferral_results(&vk, &vm_proofs, cached_commit)
-        .map_err(|e| eyre::eyre!("get_raw_deferral_results failed: {e}"))?;
-
-    let input_commits: Vec<[u8; 32]> = raw_results
-        .iter()
-        .map(|r| {
-            r.input
-                .as_slice()
-                .try_into()
-                .expect("input commit must be 32 bytes")
-        })
-        .collect();
-
-    let deferral_inputs = vec![DeferralInput::from_inputs(&vm_proofs)];
-
-    let deferral_state = get_deferral_state(&vk, &vm_proofs, cached_commit, 0)
-        .map_err(|e| eyre::eyre!("get_deferral_state failed: {e}"))?;
-
-    Ok((input_commits, deferral_inputs, vec![deferral_state]))
-}
-
 /// End-to-end test for proving witnesses of the same prover.
 #[instrument(name = "prove_verify", skip_all, fields(task_id, prover_name = prover.name()))]
 pub fn prove_verify<T: ProverTester>(
@@ -520,7 +409,7 @@ pub fn prove_verify<T: ProverTester>(
     witness: &T::Witness,
     proofs: &[ProofEnum],
 ) -> eyre::Result<ProofEnum> {
-    prove_verify_with_deferral::<T>(prover, witness, proofs, None)
+    prove_verify_with_deferral::<T>(prover, witness, proofs, false)
 }
 
 /// End-to-end test with deferred STARK verification (v2).
@@ -529,7 +418,7 @@ pub fn prove_verify_with_deferral<T: ProverTester>(
     prover: &mut impl TaskProver,
     witness: &T::Witness,
     proofs: &[ProofEnum],
-    child_prover: Option<&Prover>,
+    with_deferral: bool,
 ) -> eyre::Result<ProofEnum> {
     // Setup prover.
     let cache_dir = DIR_TESTRUN
@@ -555,18 +444,14 @@ pub fn prove_verify_with_deferral<T: ProverTester>(
             .map(|p| p.as_stark_proof().expect("must be stark proof"))
             .collect();
 
-        let (input_commits, def_inputs, def_states) = if let Some(child) = child_prover {
-            compute_deferral_data(child, prover.deferral_cached_commit()?, &stark_proofs)?
-        } else {
-            (vec![], vec![], vec![])
-        };
-
-        let task = T::build_universal_task(witness, stark_proofs.into_iter(), input_commits)?;
+        // The input commits of an aggregation task are computed by the prover from its child
+        // proofs.
+        let task = T::build_universal_task(witness, stark_proofs.into_iter(), vec![])?;
         // Construct stark proof for the circuit.
-        let proof = if def_inputs.is_empty() {
-            prover.prove_task(&task, false)?
+        let proof = if with_deferral {
+            prover.prove_aggregation_task(&task, false)?
         } else {
-            prover.prove_task_with_deferral(&task, false, &def_inputs, &def_states)?
+            prover.prove_task(&task, false)?
         };
         write_json(&path_proof, &proof)?;
         tracing::debug!(name: "cached_proof", ?task_id);
@@ -576,10 +461,11 @@ pub fn prove_verify_with_deferral<T: ProverTester>(
 
     // Verify proof using the prover's own aggregation VK (required for deferral-enabled circuits).
     let agg_vk = prover.get_agg_vk()?;
-    UniversalVerifier::verify_stark_proof_with_vk(
+    UniversalVerifier::verify_stark_proof_strict_with_vk(
         &agg_vk,
         proof.as_stark_proof().expect("should be stark proof"),
         &vk,
+        prover.get_def_hook_commit()?,
     )?;
 
     Ok(proof)
@@ -595,7 +481,7 @@ pub fn prove_verify_single_evm<T>(
 where
     T: ProverTester,
 {
-    prove_verify_single_evm_with_deferral::<T>(prover, witness, proofs, None)
+    prove_verify_single_evm_with_deferral::<T>(prover, witness, proofs, false)
 }
 
 /// End-to-end EVM proof with deferred STARK verification (v2).
@@ -604,7 +490,7 @@ pub fn prove_verify_single_evm_with_deferral<T>(
     prover: &mut impl TaskProver,
     witness: &T::Witness,
     proofs: &[ProofEnum],
-    child_prover: Option<&Prover>,
+    with_deferral: bool,
 ) -> eyre::Result<ProofEnum>
 where
     T: ProverTester,
@@ -636,18 +522,14 @@ where
             .map(|p| p.as_stark_proof().expect("must be stark proof"))
             .collect();
 
-        let (input_commits, def_inputs, def_states) = if let Some(child) = child_prover {
-            compute_deferral_data(child, prover.deferral_cached_commit()?, &stark_proofs)?
-        } else {
-            (vec![], vec![], vec![])
-        };
-
-        let task = T::build_universal_task(witness, stark_proofs.into_iter(), input_commits)?;
+        // The input commits of an aggregation task are computed by the prover from its child
+        // proofs.
+        let task = T::build_universal_task(witness, stark_proofs.into_iter(), vec![])?;
         // Construct stark proof for the circuit.
-        let proof = if def_inputs.is_empty() {
-            prover.prove_task(&task, true)?
+        let proof = if with_deferral {
+            prover.prove_aggregation_task(&task, true)?
         } else {
-            prover.prove_task_with_deferral(&task, true, &def_inputs, &def_states)?
+            prover.prove_task(&task, true)?
         };
         write_json(&path_proof, &proof)?;
         tracing::debug!(name: "cached_evm_proof", ?task_id);
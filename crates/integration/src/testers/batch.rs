@@ -84,12 +84,8 @@ impl BatchTaskGenerator {
         let wit = self.get_or_build_witness()?;
         let agg_proofs = self.get_or_build_child_proofs(child_prover)?;
         prover.enable_deferral(child_prover)?;
-        let proof = prove_verify_with_deferral::<BatchProverTester>(
-            prover,
-            &wit,
-            &agg_proofs,
-            Some(child_prover),
-        )?;
+        let proof =
+            prove_verify_with_deferral::<BatchProverTester>(prover, &wit, &agg_proofs, true)?;
         self.proof.replace(proof.clone());
         Ok(proof)
     }
@@ -10,7 +10,8 @@ use scroll_zkvm_types::{
 // Only related to hardcoded commitments. Can be refactored later.
 use crate::{
     PROGRAM_COMMITMENTS, PartialProvingTask, ProverTester, prove_verify_single_evm_with_deferral,
-    testers::batch::BatchTaskGenerator, utils::metadata_from_batch_witnesses,
+    prove_verify_with_deferral, testers::batch::BatchTaskGenerator,
+    utils::metadata_from_batch_witnesses,
 };
 use scroll_zkvm_prover::Prover;
 
@@ -81,13 +82,29 @@ impl BundleTaskGenerator {
             prover,
             &wit,
             &agg_proofs,
-            Some(batch_prover),
+            true,
         )?;
         self.proof.replace(proof.clone());
         Ok(proof)
     }
 
-    fn get_or_build_child_proofs(
+    /// Build the STARK proof of the bundle, e.g. to wrap it separately, see
+    /// [`Prover::wrap_to_evm`].
+    pub fn build_stark_proof(
+        &mut self,
+        prover: &mut Prover,
+        batch_prover: &mut Prover,
+        chunk_prover: &mut Prover,
+    ) -> eyre::Result<ProofEnum> {
+        let wit = self.get_or_build_witness()?;
+        let agg_proofs = self.get_or_build_child_proofs(batch_prover, chunk_prover)?;
+        prover.enable_deferral(batch_prover)?;
+        chunk_prover.reset();
+        batch_prover.reset();
+        prove_verify_with_deferral::<BundleProverTester>(prover, &wit, &agg_proofs, true)
+    }
+
+    pub fn get_or_build_child_proofs(
         &mut self,
         batch_prover: &mut Prover,
         chunk_prover: &mut Prover,
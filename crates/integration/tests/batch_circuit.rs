@@ -7,8 +7,8 @@ use scroll_zkvm_integration::{
     },
     testing_version,
 };
-use scroll_zkvm_prover::task::ProvingTask;
-use scroll_zkvm_types::public_inputs::Version;
+use scroll_zkvm_prover::{AdmissionError, Error, task::ProvingTask};
+use scroll_zkvm_types::{proof::StarkProof, public_inputs::Version};
 
 #[ignore = "need local stuff"]
 #[test]
@@ -69,6 +69,67 @@ fn e2e() -> eyre::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_input_commits_mismatch() -> eyre::Result<()> {
+    BatchProverTester::setup(true)?;
+
+    let mut prover = BatchProverTester::load_prover(false)?;
+    let mut chunk_prover = ChunkProverTester::load_prover(false)?;
+    let mut batch = BatchTaskGenerator::from_chunk_tasks(&preset_chunk_multiple(), None);
+    let wit = batch.get_or_build_witness()?;
+    let child_proofs = batch
+        .get_or_build_child_proofs(&mut chunk_prover)?
+        .iter()
+        .map(|proof| proof.as_stark_proof().expect("stark proof").clone())
+        .collect::<Vec<StarkProof>>();
+    prover.enable_deferral(&chunk_prover)?;
+
+    // The task carries the commits of all the child proofs, but only some are aggregated.
+    let input_commits = prover.compute_deferral_data(&child_proofs)?.input_commits;
+    let task = BatchProverTester::build_universal_task(&wit, child_proofs.iter(), input_commits)?;
+    let result = prover.prove_aggregation(&task, &child_proofs[1..], false);
+    assert!(
+        matches!(
+            result,
+            Err(Error::Admission(AdmissionError::InputCommitsMismatch))
+        ),
+        "{:?}",
+        result.err()
+    );
+
+    // The task carries commits of other proofs.
+    let task = BatchProverTester::build_universal_task(
+        &wit,
+        child_proofs.iter(),
+        vec![[0; 32]; child_proofs.len()],
+    )?;
+    let result = prover.prove_aggregation(&task, &child_proofs, false);
+    assert!(
+        matches!(
+            result,
+            Err(Error::Admission(AdmissionError::InputCommitsMismatch))
+        ),
+        "{:?}",
+        result.err()
+    );
+
+    // A misrouted task is rejected before the deferral data of its children is computed, which
+    // would fail on the missing child proofs.
+    let mut task = BatchProverTester::build_universal_task(&wit, child_proofs.iter(), vec![])?;
+    task.vk = vec![0; prover.get_app_vk().len()];
+    let result = prover.prove_aggregation(&task, &[], false);
+    assert!(
+        matches!(
+            result,
+            Err(Error::Admission(AdmissionError::VkMismatch { .. }))
+        ),
+        "{:?}",
+        result.err()
+    );
+
+    Ok(())
+}
+
 #[test]
 fn verify_batch_hash_invariant() -> eyre::Result<()> {
     use scroll_zkvm_types::public_inputs::ForkName;
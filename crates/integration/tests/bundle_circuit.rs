@@ -10,12 +10,13 @@ use scroll_zkvm_integration::{
     testing_version, testing_version_validium,
     utils::metadata_from_bundle_witnesses,
 };
-use scroll_zkvm_prover::{Prover, ProverConfig};
+use scroll_zkvm_prover::{Prover, setup::compute_app_vk};
 use scroll_zkvm_types::version::Version;
 use scroll_zkvm_types::{
-    proof::OpenVmEvmProof,
-    public_inputs::{ForkName, MultiVersionPublicInputs, PublicInputs},
+    proof::{OpenVmEvmProof, StarkProof},
+    public_inputs::{ForkName, MultiVersionPublicInputs, PublicInputs, scroll::bundle::BundleInfo},
 };
+use scroll_zkvm_verifier::{VerifyError, verifier::UniversalVerifier};
 use std::str::FromStr;
 
 fn preset_bundle() -> BundleTaskGenerator {
@@ -38,23 +39,19 @@ fn print_vks() -> eyre::Result<()> {
         let dev_mode = true;
         let (path_app_exe, path_app_config) = if dev_mode {
             (
-                format!("../../crates/circuits/{circuit}-circuit/openvm/app.vmexe").into(),
-                format!("../../crates/circuits/{circuit}-circuit/openvm.toml").into(),
+                format!("../../crates/circuits/{circuit}-circuit/openvm/app.vmexe"),
+                format!("../../crates/circuits/{circuit}-circuit/openvm.toml"),
             )
         } else {
             let version = "0.5.0";
             (
-                format!("../../{version}/{circuit}/app.vmexe").into(),
-                format!("../../{version}/{circuit}/openvm.toml").into(),
+                format!("../../{version}/{circuit}/app.vmexe"),
+                format!("../../{version}/{circuit}/openvm.toml"),
             )
         };
 
-        let config = ProverConfig {
-            path_app_exe,
-            path_app_config,
-        };
-
-        let app_vk = hex::encode(Prover::setup(config, None).unwrap().get_app_vk());
+        // The vks are computed without setting up the provers.
+        let app_vk = hex::encode(compute_app_vk(path_app_exe, path_app_config).unwrap());
         println!("{circuit}: {app_vk}");
         app_vk
     });
@@ -132,6 +129,76 @@ fn e2e() -> eyre::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_wrap_to_evm() -> eyre::Result<()> {
+    BundleProverTester::setup(true)?;
+
+    let mut chunk_prover = ChunkProverTester::load_prover(false)?;
+    let mut batch_prover = BatchProverTester::load_prover(false)?;
+    let mut bundle_prover = BundleProverTester::load_prover(true)?;
+    let proof = preset_bundle().build_stark_proof(
+        &mut bundle_prover,
+        &mut batch_prover,
+        &mut chunk_prover,
+    )?;
+    let stark_proof = proof.as_stark_proof().expect("bundle stark proof");
+
+    let evm_proof = bundle_prover.wrap_to_evm(stark_proof)?;
+    let stat = evm_proof.stat.as_ref().expect("stat of wrapped proof");
+    assert_eq!(stat.total_cycles, stark_proof.stat.total_cycles);
+
+    let verifier = UniversalVerifier::setup(ASSET_BASE_DIR.join("verifier"))?;
+    verifier.verify_evm_proof(&evm_proof.into(), &bundle_prover.get_app_vk())?;
+
+    Ok(())
+}
+
+#[test]
+fn test_deferral_from_assets() -> eyre::Result<()> {
+    BundleProverTester::setup(true)?;
+
+    let mut chunk_prover = ChunkProverTester::load_prover(false)?;
+    let mut batch_prover = BatchProverTester::load_prover(false)?;
+    batch_prover.enable_deferral(&chunk_prover)?;
+    let mut bundle_prover = BundleProverTester::load_prover(false)?;
+    bundle_prover.enable_deferral(&batch_prover)?;
+
+    // Only the assets of the batch circuit are needed, not a batch prover.
+    let mut bundle_prover_from_assets = BundleProverTester::load_prover(false)?;
+    bundle_prover_from_assets
+        .enable_deferral_from_assets(ASSET_BASE_DIR.join(BatchProverTester::NAME))?;
+
+    let vk = bundle_prover.get_app_vk();
+    let def_hook_commit = bundle_prover.def_hook_commit()?;
+    assert_eq!(bundle_prover_from_assets.get_app_vk(), vk);
+    assert_eq!(
+        bundle_prover_from_assets.def_hook_commit()?,
+        def_hook_commit
+    );
+    bundle_prover.reset();
+
+    // The bundle prover set up from the assets proves bundles of the batch prover's proofs.
+    let mut task = preset_bundle();
+    let wit = task.get_or_build_witness()?;
+    let child_proofs = task
+        .get_or_build_child_proofs(&mut batch_prover, &mut chunk_prover)?
+        .iter()
+        .map(|proof| proof.as_stark_proof().expect("stark proof").clone())
+        .collect::<Vec<StarkProof>>();
+    chunk_prover.reset();
+    batch_prover.reset();
+    let task = BundleProverTester::build_universal_task(&wit, child_proofs.iter(), vec![])?;
+    let proof = bundle_prover_from_assets.prove_aggregation(&task, &child_proofs, false)?;
+    UniversalVerifier::verify_stark_proof_strict_with_vk(
+        &bundle_prover_from_assets.load_agg_vk()?,
+        proof.as_stark_proof().expect("stark proof"),
+        &vk,
+        def_hook_commit,
+    )?;
+
+    Ok(())
+}
+
 fn e2e_inner(
     chunk_prover: &mut Prover,
     batch_prover: &mut Prover,
@@ -199,6 +266,19 @@ fn e2e_inner(
         "digest_2.hex does not match the digest in the proof instances; expected canonical form"
     );
 
+    // The proof must prove this very bundle, and no other.
+    let verifier = UniversalVerifier::setup(ASSET_BASE_DIR.join("verifier"))?;
+    let vk = bundle_prover.get_app_vk();
+    verifier.verify_evm_proof_with_pi(&inner_evm_proof, &vk, &metadata, version)?;
+    let other_bundle = BundleInfo {
+        num_batches: metadata.num_batches + 1,
+        ..metadata.clone()
+    };
+    assert!(matches!(
+        verifier.verify_evm_proof_with_pi(&inner_evm_proof, &vk, &other_bundle, version),
+        Err(VerifyError::PiHashMismatch { .. })
+    ));
+
     let evm_proof: OpenVmEvmProof = inner_evm_proof.into();
 
     let observed_instances = &evm_proof.user_public_values;
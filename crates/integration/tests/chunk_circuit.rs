@@ -1,10 +1,13 @@
 use alloy_primitives::B256;
 use eyre::Ok;
+use openvm_stark_sdk::openvm_stark_backend::p3_field::PrimeField32;
+use openvm_verify_stark_host::vk::VerificationBaseline;
 use sbv_primitives::types::consensus::TxL1Message;
 use scroll_zkvm_integration::testers::PATH_TESTDATA;
 use scroll_zkvm_integration::testers::chunk::read_block_witness;
 use scroll_zkvm_integration::testers::chunk::{exec_chunk, execute_multi};
 use scroll_zkvm_integration::utils::get_rayon_threads;
+use scroll_zkvm_integration::{ASSET_BASE_DIR, DIR_TESTRUN, GUEST_VERSION, testing_version};
 use scroll_zkvm_integration::{
     ProverTester, prove_verify,
     testers::chunk::{
@@ -13,11 +16,25 @@ use scroll_zkvm_integration::{
     },
     utils::metadata_from_chunk_witnesses,
 };
-use scroll_zkvm_prover::utils::read_json;
-use scroll_zkvm_types::public_inputs::Version;
-use scroll_zkvm_types::scroll::chunk::{ChunkWitness, SecretKey};
+use scroll_zkvm_prover::{
+    AdmissionError, Error, Prover, ProverConfig,
+    circuits::CircuitConfig,
+    crash,
+    keys::shared_agg_pk,
+    pool::{PoolConfig, ProverPool},
+    registry::{ProverRegistry, ReleaseConfig},
+    setup::compute_app_vk,
+    utils::read_json,
+};
+use scroll_zkvm_types::proof::StarkProof;
+use scroll_zkvm_types::public_inputs::{MultiVersionPublicInputs, Version};
+use scroll_zkvm_types::scroll::chunk::{ChunkInfo, ChunkWitness, SecretKey};
+use scroll_zkvm_verifier::{VerifyError, verifier::UniversalVerifier};
+use std::collections::HashMap;
 use std::env;
 use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 
 #[ignore = "can only run under eculidv2 hardfork"]
 #[test]
@@ -184,6 +201,275 @@ fn guest_profiling() -> eyre::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_dry_run() -> eyre::Result<()> {
+    ChunkProverTester::setup(true)?;
+    let prover = ChunkProverTester::load_prover(false)?;
+
+    let wit = get_witness_from_env_or_builder(&mut preset_chunk())?;
+    let task = ChunkProverTester::build_universal_task(&wit, std::iter::empty(), vec![])?;
+    let report = prover.dry_run_chunk(&task)?;
+    println!("dry run report: {}", serde_json::to_string_pretty(&report)?);
+
+    assert!(report.num_segments > 0);
+    assert_eq!(report.num_segments, report.segment_cycles.len());
+    let chunk = report.chunk.as_ref().expect("chunk check for chunk task");
+    let chunk_info = metadata_from_chunk_witnesses(wit)?;
+    assert_eq!(
+        chunk.expected_pi_hash,
+        chunk_info.pi_hash_by_version(chunk.version).0
+    );
+    assert!(chunk.pi_hash_matches);
+
+    // A witness of an unknown version is rejected before executing it.
+    let mut task = task;
+    task.serialized_witness[0][0] = 0xff;
+    assert!(matches!(
+        prover.dry_run_chunk(&task),
+        Err(Error::Admission(AdmissionError::UnknownVersion(0xff)))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_crash_bundle() -> eyre::Result<()> {
+    ChunkProverTester::setup(true)?;
+    let mut prover = ChunkProverTester::load_prover(false)?;
+    let dir_crash = DIR_TESTRUN
+        .get()
+        .ok_or(eyre::eyre!("missing testrun dir"))?
+        .join("crashes");
+    prover.config.dir_crash = Some(dir_crash.clone());
+
+    // A truncated witness fails to deserialize in the guest.
+    let wit = get_witness_from_env_or_builder(&mut preset_chunk())?;
+    let mut task = ChunkProverTester::build_universal_task(&wit, std::iter::empty(), vec![])?;
+    let witness = &mut task.serialized_witness[0];
+    witness.truncate(witness.len() / 2);
+    assert!(prover.dry_run(&task).is_err());
+    // Failing again within the same second writes another bundle.
+    assert!(prover.dry_run(&task).is_err());
+
+    let bundles = std::fs::read_dir(&dir_crash)?.collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(bundles.len(), 2);
+    let dir_bundle = bundles[0].path();
+    for fd in [
+        crash::FD_CRASH_INFO,
+        crash::FD_TASK,
+        crash::FD_STDIN,
+        crash::FD_STDIN_BIN,
+        crash::FD_GUEST_STDOUT,
+        crash::FD_REPLAY,
+    ] {
+        assert!(dir_bundle.join(fd).exists(), "missing {fd}");
+    }
+    // Chunk tasks aggregate no child proofs.
+    assert!(!dir_bundle.join(crash::FD_DEF_INPUTS).exists());
+    let info: crash::CrashInfo = read_json(dir_bundle.join(crash::FD_CRASH_INFO))?;
+    assert_eq!(info.task_id, task.identifier);
+    assert_eq!(info.app_vk, hex::encode(prover.get_app_vk()));
+    assert_eq!(
+        info.guest_release,
+        prover.config.release.clone().unwrap_or_default()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_compute_app_vk() -> eyre::Result<()> {
+    ChunkProverTester::setup(true)?;
+    let (path_app_config, path_app_exe) = ChunkProverTester::load()?;
+
+    let vk = compute_app_vk(&path_app_exe, &path_app_config)?;
+    let prover = ChunkProverTester::load_prover(false)?;
+    assert_eq!(vk, prover.get_app_vk());
+
+    // The commitment computed from the app keys only matches the one of the whole SDK.
+    let sdk_prover = prover.sdk()?.prover(prover.app_exe.clone())?;
+    let exe_digest = sdk_prover.app_prover.app_exe_commit();
+    let vm_digest = sdk_prover.app_vm_commit();
+    let exe: [u32; 8] = std::array::from_fn(|i| exe_digest[i].as_canonical_u32());
+    let vm: [u32; 8] = std::array::from_fn(|i| vm_digest[i].as_canonical_u32());
+    let commitment = prover.get_app_commitment();
+    assert_eq!((commitment.exe, commitment.vm), (exe, vm));
+
+    Ok(())
+}
+
+#[test]
+fn test_registry_routing() -> eyre::Result<()> {
+    ChunkProverTester::setup(true)?;
+
+    let release = ReleaseConfig {
+        name: GUEST_VERSION.to_string(),
+        forks: vec![],
+        circuits: vec![CircuitConfig {
+            name: "chunk".to_string(),
+            dir: ASSET_BASE_DIR.join("chunk"),
+            child: None,
+            child_dir: None,
+        }],
+    };
+    let registry = ProverRegistry::new(vec![release], None, None, Some(Duration::ZERO))?;
+    let (_, _, vk) = registry
+        .circuits()
+        .next()
+        .expect("chunk circuit registered");
+    let vk = vk.to_vec();
+
+    let wit = get_witness_from_env_or_builder(&mut preset_chunk())?;
+    let mut task = ChunkProverTester::build_universal_task(&wit, std::iter::empty(), vec![])?;
+    assert!(matches!(
+        registry.route(&task),
+        Err(Error::Admission(AdmissionError::MissingVk))
+    ));
+
+    task.vk = vk.clone();
+    let prover = registry.route(&task)?;
+    assert_eq!(prover.get_app_vk(), vk);
+    assert_eq!(registry.loaded().count(), 1);
+    assert_eq!(registry.evict_idle(), vec![GUEST_VERSION.to_string()]);
+    // The routed prover outlives the unloading of its release.
+    assert_eq!(registry.loaded().count(), 0);
+    assert_eq!(prover.get_app_vk(), vk);
+
+    task.vk = vec![0; vk.len()];
+    assert!(matches!(
+        registry.route(&task),
+        Err(Error::Admission(AdmissionError::NoProver { .. }))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_shared_agg_pk() -> eyre::Result<()> {
+    ChunkProverTester::setup(true)?;
+
+    // Two provers with the same aggregation config share the aggregation proving key.
+    let prover_1 = ChunkProverTester::load_prover(false)?;
+    let prover_2 = ChunkProverTester::load_prover(false)?;
+    assert_eq!(prover_1.agg_config(), prover_2.agg_config());
+    prover_1.sdk()?;
+    let shared_1 = shared_agg_pk(prover_1.agg_config(), None).expect("key shared by prover 1");
+    prover_2.sdk()?;
+    let shared_2 = shared_agg_pk(prover_2.agg_config(), None).expect("key shared by prover 2");
+    assert!(Arc::ptr_eq(&shared_1, &shared_2));
+
+    // The second prover does not allocate another key: both SDKs hold the shared one.
+    let agg_pk_1 = prover_1.sdk()?.agg_pk_shared();
+    let agg_pk_2 = prover_2.sdk()?.agg_pk_shared();
+    assert!(Arc::ptr_eq(&agg_pk_1, &shared_1));
+    assert!(Arc::ptr_eq(&agg_pk_2, &shared_1));
+
+    // The key is held by the SDKs: resetting one prover keeps it shared by the other.
+    let shared = Arc::downgrade(&shared_1);
+    drop((shared_1, shared_2, agg_pk_1, agg_pk_2));
+    prover_1.reset();
+    assert!(shared.upgrade().is_some());
+
+    Ok(())
+}
+
+#[test]
+fn test_prover_pool() -> eyre::Result<()> {
+    ChunkProverTester::setup(true)?;
+    let prover = Arc::new(ChunkProverTester::load_prover(false)?);
+
+    let tasks = preset_chunk_multiple()
+        .into_iter()
+        .map(|mut task| {
+            ChunkProverTester::build_universal_task(
+                &task.get_or_build_witness()?,
+                std::iter::empty(),
+                vec![],
+            )
+        })
+        .collect::<eyre::Result<Vec<_>>>()?;
+
+    let pool = ProverPool::new(
+        prover.clone(),
+        PoolConfig {
+            num_workers: 2,
+            memory_budget_bytes: 2 << 30,
+            task_memory_bytes: 1 << 30,
+        },
+    )?;
+    assert_eq!(pool.parallelism(), 2);
+
+    // Invalid configs are rejected for good, not as resources to wait for.
+    for config in [
+        PoolConfig {
+            num_workers: 0,
+            memory_budget_bytes: 2 << 30,
+            task_memory_bytes: 1 << 30,
+        },
+        PoolConfig {
+            num_workers: 2,
+            memory_budget_bytes: 1 << 30,
+            task_memory_bytes: 2 << 30,
+        },
+    ] {
+        let err = ProverPool::new(prover.clone(), config)
+            .err()
+            .expect("invalid pool config");
+        assert!(!err.is_retryable(), "{err}");
+    }
+
+    let vk = prover.get_app_vk();
+    let agg_vk = prover.load_agg_vk()?;
+    for result in pool.prove_all(&tasks, false) {
+        let proof = result?;
+        UniversalVerifier::verify_stark_proof_with_vk(
+            &agg_vk,
+            proof.as_stark_proof().expect("stark proof"),
+            &vk,
+        )?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_key_cache() -> eyre::Result<()> {
+    ChunkProverTester::setup(true)?;
+    let (path_app_config, path_app_exe) = ChunkProverTester::load()?;
+    let dir_keys = DIR_TESTRUN
+        .get()
+        .ok_or(eyre::eyre!("missing testrun dir"))?
+        .join("keys");
+    let config = ProverConfig {
+        path_app_exe,
+        path_app_config,
+        dir_keys: Some(dir_keys.clone()),
+        ..Default::default()
+    };
+
+    let wit = get_witness_from_env_or_builder(&mut preset_chunk())?;
+    let task = ChunkProverTester::build_universal_task(&wit, std::iter::empty(), vec![])?;
+
+    // The first prover generates the keys and caches them.
+    let prover = Prover::setup(config.clone(), Some(ChunkProverTester::NAME))?;
+    let vk = prover.get_app_vk();
+    prover.gen_proof_universal(&task, false)?;
+    assert_eq!(std::fs::read_dir(&dir_keys)?.count(), 1);
+    drop(prover);
+
+    // The second prover loads the cached keys.
+    let prover = Prover::setup(config, Some(ChunkProverTester::NAME))?;
+    assert_eq!(prover.get_app_vk(), vk);
+    let proof = prover.gen_proof_universal(&task, false)?;
+    UniversalVerifier::verify_stark_proof_with_vk(
+        &prover.load_agg_vk()?,
+        proof.as_stark_proof().expect("stark proof"),
+        &vk,
+    )?;
+
+    Ok(())
+}
+
 #[test]
 fn setup_prove_verify_single() -> eyre::Result<()> {
     ChunkProverTester::setup(true)?;
@@ -195,6 +481,93 @@ fn setup_prove_verify_single() -> eyre::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_strict_stark_verification() -> eyre::Result<()> {
+    ChunkProverTester::setup(true)?;
+    let mut prover = ChunkProverTester::load_prover(false)?;
+
+    let wit = get_witness_from_env_or_builder(&mut preset_chunk())?;
+    let proof = prove_verify::<ChunkProverTester>(&mut prover, &wit, &[])?;
+    let proof = proof.as_stark_proof().expect("stark proof");
+    let vk = prover.get_app_vk();
+    let agg_vk = prover.load_agg_vk()?;
+    let def_hook_commit = prover.def_hook_commit()?;
+    UniversalVerifier::verify_stark_proof_strict_with_vk(
+        &agg_vk,
+        proof,
+        &vk,
+        def_hook_commit.clone(),
+    )?;
+
+    // The baseline of the proof is the one expected from the vk, i.e. the vm commit of the vk
+    // is the app vk commit of the baseline.
+    let baseline: VerificationBaseline = serde_json::from_slice(&proof.baseline)?;
+    let expected = UniversalVerifier::expected_baseline(&vk, def_hook_commit.clone());
+    assert_eq!(
+        serde_json::to_value(&baseline)?,
+        serde_json::to_value(&expected)?
+    );
+
+    // A baseline tampering any of its commits is rejected.
+    let tamperings: [fn(&mut VerificationBaseline); 3] = [
+        |baseline| baseline.app_exe_commit = baseline.app_vk_commit,
+        |baseline| baseline.app_vk_commit = baseline.app_exe_commit,
+        // A deferral hook the chunk circuit does not have.
+        |baseline| baseline.def_hook_commit = Some(baseline.app_exe_commit),
+    ];
+    for tamper in tamperings {
+        let mut baseline = baseline.clone();
+        tamper(&mut baseline);
+        let tampered = StarkProof {
+            baseline: serde_json::to_vec(&baseline)?,
+            ..proof.clone()
+        };
+        assert!(
+            UniversalVerifier::verify_stark_proof_strict_with_vk(
+                &agg_vk,
+                &tampered,
+                &vk,
+                def_hook_commit.clone()
+            )
+            .is_err()
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_with_pi() -> eyre::Result<()> {
+    ChunkProverTester::setup(true)?;
+    let mut prover = ChunkProverTester::load_prover(false)?;
+
+    let wit = get_witness_from_env_or_builder(&mut preset_chunk())?;
+    let proof = prove_verify::<ChunkProverTester>(&mut prover, &wit, &[])?;
+    let proof = proof.as_stark_proof().expect("stark proof");
+    let vk = prover.get_app_vk();
+    let verifier = UniversalVerifier {
+        evm_verifier: vec![],
+        loaded_mvk: prover.load_agg_vk()?,
+        def_hook_commits: HashMap::from([(vk.clone(), prover.def_hook_commit()?)]),
+    };
+    let chunk_info = metadata_from_chunk_witnesses(wit)?;
+    let version = testing_version();
+
+    verifier.verify_stark_proof_with_pi(proof, &vk, &chunk_info, version)?;
+
+    // A valid proof of another chunk is rejected.
+    let other_chunk = ChunkInfo {
+        chain_id: chunk_info.chain_id + 1,
+        ..chunk_info.clone()
+    };
+    assert!(matches!(
+        verifier.verify_stark_proof_with_pi(proof, &vk, &other_chunk, version),
+        Err(VerifyError::PiHashMismatch { .. })
+    ));
+
+    Ok(())
+}
+
 #[test]
 fn setup_prove_verify_multi() -> eyre::Result<()> {
     ChunkProverTester::setup(true)?;
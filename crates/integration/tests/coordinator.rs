@@ -0,0 +1,122 @@
+use std::{sync::Arc, time::Duration};
+
+use scroll_zkvm_integration::{
+    PartialProvingTask, ProverTester, TaskProver,
+    coordinator::MockCoordinator,
+    testers::{
+        batch::{BatchProverTester, BatchTaskGenerator},
+        chunk::{ChunkProverTester, preset_chunk, preset_chunk_multiple},
+    },
+};
+use scroll_zkvm_prover::{
+    circuits::Circuit,
+    coordinator::{AssignedTask, Backoff, Client, ClientConfig},
+};
+use scroll_zkvm_verifier::verifier::UniversalVerifier;
+
+fn client_config() -> ClientConfig {
+    ClientConfig {
+        prover_name: "test-prover".to_string(),
+        poll_interval: Duration::from_millis(100),
+        heartbeat_interval: Duration::from_secs(1),
+        backoff: Backoff {
+            initial: Duration::from_millis(100),
+            max: Duration::from_secs(1),
+            max_retries: 3,
+        },
+    }
+}
+
+#[test]
+fn pull_loop_chunk() -> eyre::Result<()> {
+    ChunkProverTester::setup(true)?;
+    let mut prover = ChunkProverTester::load_prover(false)?;
+    let vk = prover.get_vk()?;
+    let agg_vk = prover.get_agg_vk()?;
+
+    let mut generators = [preset_chunk()];
+    let coordinator = Arc::new(MockCoordinator::from_chunk_fixtures(&mut generators, &vk)?);
+    let task_id = generators[0].get_or_build_witness()?.identifier();
+
+    let circuit = Circuit {
+        name: ChunkProverTester::NAME.to_string(),
+        vk: vk.clone(),
+        prover,
+    };
+    let mut client = Client::new(coordinator.clone(), vec![circuit], client_config());
+
+    // Sessions expiring must be handled by logging in again.
+    assert!(client.run_once()?, "the chunk task should be assigned");
+    coordinator.expire_tokens();
+    assert!(!client.run_once()?, "no task should be left");
+    assert_eq!(coordinator.num_pending(), 0);
+
+    assert_eq!(coordinator.failure(&task_id), None);
+    let proof = coordinator
+        .proof(&task_id)
+        .ok_or_else(|| eyre::eyre!("no proof submitted for {task_id}"))?;
+    UniversalVerifier::verify_stark_proof_strict_with_vk(
+        &agg_vk,
+        proof.as_stark_proof().expect("should be stark proof"),
+        &vk,
+        None,
+    )?;
+
+    Ok(())
+}
+
+#[test]
+fn pull_loop_batch() -> eyre::Result<()> {
+    BatchProverTester::setup(true)?;
+    let mut prover = BatchProverTester::load_prover(false)?;
+    let mut chunk_prover = ChunkProverTester::load_prover(false)?;
+
+    let mut batch = BatchTaskGenerator::from_chunk_tasks(&preset_chunk_multiple(), None);
+    let witness = batch.get_or_build_witness()?;
+    let chunk_proofs = batch.get_or_build_child_proofs(&mut chunk_prover)?;
+    prover.enable_deferral(&chunk_prover)?;
+    let vk = prover.get_vk()?;
+    let agg_vk = prover.get_agg_vk()?;
+    let def_hook_commit = prover.get_def_hook_commit()?;
+
+    // The chunk proofs are fetched from the coordinator as the child proofs of the batch task.
+    let coordinator = Arc::new(MockCoordinator::new());
+    let mut child_task_ids = Vec::new();
+    for (i, proof) in chunk_proofs.into_iter().enumerate() {
+        let child_task_id = format!("chunk-{i}");
+        coordinator.insert_proof(&child_task_id, proof);
+        child_task_ids.push(child_task_id);
+    }
+    let mut task = BatchProverTester::build_universal_task(&witness, std::iter::empty(), vec![])?;
+    task.vk = vk.clone();
+    let task_id = task.identifier.clone();
+    coordinator.push_task(AssignedTask {
+        task_id: task_id.clone(),
+        circuit: BatchProverTester::NAME.to_string(),
+        with_snark: false,
+        child_task_ids,
+        task,
+    });
+
+    let circuit = Circuit {
+        name: BatchProverTester::NAME.to_string(),
+        vk: vk.clone(),
+        prover,
+    };
+    let mut client = Client::new(coordinator.clone(), vec![circuit], client_config());
+    assert!(client.run_once()?, "the batch task should be assigned");
+    assert_eq!(coordinator.num_pending(), 0);
+
+    assert_eq!(coordinator.failure(&task_id), None);
+    let proof = coordinator
+        .proof(&task_id)
+        .ok_or_else(|| eyre::eyre!("no proof submitted for {task_id}"))?;
+    UniversalVerifier::verify_stark_proof_strict_with_vk(
+        &agg_vk,
+        proof.as_stark_proof().expect("should be stark proof"),
+        &vk,
+        def_hook_commit,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+//! Prover daemon serving a local HTTP job API, see [`scroll_zkvm_prover::server`].
+use std::path::PathBuf;
+
+use clap::Parser;
+use scroll_zkvm_prover::server::{ServerConfig, init_tracing, run};
+
+#[derive(Parser)]
+#[command(version, about = "Scroll zkVM prover daemon")]
+struct Cli {
+    /// Path to the TOML configuration of the daemon.
+    #[arg(long, short)]
+    config: PathBuf,
+    /// Override the address to listen on.
+    #[arg(long)]
+    listen: Option<String>,
+}
+
+fn main() -> eyre::Result<()> {
+    init_tracing();
+
+    let cli = Cli::parse();
+    let mut config = ServerConfig::from_file(&cli.config)?;
+    if let Some(listen) = cli.listen {
+        config.listen = listen;
+    }
+
+    run(config)?;
+    Ok(())
+}
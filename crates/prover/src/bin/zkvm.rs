@@ -0,0 +1,296 @@
+//! Command line tool to execute, prove, verify and inspect tasks and proofs of a circuit.
+use std::path::{Path, PathBuf};
+
+use clap::{Args, Parser, Subcommand};
+use openvm_sdk::{SC, StdIn};
+use openvm_stark_sdk::openvm_stark_backend::{
+    keygen::types::MultiStarkVerifyingKey, p3_field::PrimeField32,
+};
+use scroll_zkvm_prover::{
+    Prover, ProverConfig, crash,
+    setup::compute_app_vk,
+    utils::{read_json_deep, vm::decode_stark_proof, write_json},
+};
+use scroll_zkvm_types::{
+    proof::{OpenVmEvmProof, ProofEnum},
+    task::ProvingTask,
+    types_agg::ProgramCommitment,
+    utils::serialize_vk,
+};
+use scroll_zkvm_verifier::verifier::{
+    UniversalVerifier, public_values_as_bytes, read_def_hook_commit,
+};
+use serde_json::json;
+
+#[derive(Parser)]
+#[command(version, about = "Scroll zkVM command line tool")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Execute a task without proving it, printing its dry run report.
+    Execute {
+        #[command(flatten)]
+        circuit: CircuitArgs,
+        /// Path to the universal task (JSON).
+        #[arg(long)]
+        task: PathBuf,
+    },
+    /// Execute the guest input of a crash bundle again, printing its cycle count.
+    Replay {
+        /// Directory of the crash bundle.
+        bundle: PathBuf,
+    },
+    /// Prove a task, writing the proof as JSON.
+    Prove {
+        #[command(flatten)]
+        circuit: CircuitArgs,
+        /// Path to the universal task (JSON).
+        #[arg(long)]
+        task: PathBuf,
+        /// Generate an EVM proof instead of a STARK proof.
+        #[arg(long)]
+        snark: bool,
+        /// Path to write the proof to.
+        #[arg(long, short, default_value = "proof.json")]
+        out: PathBuf,
+    },
+    /// Verify a proof of a circuit.
+    Verify {
+        /// Directory holding the circuit's `app.vmexe`, `openvm.toml`, `agg_vk.bin` and
+        /// `def_hook_commit.json`.
+        #[arg(long)]
+        circuit: PathBuf,
+        /// Path to the proof (JSON).
+        #[arg(long)]
+        proof: PathBuf,
+        /// Hex-encoded app vk the proof must have. Computed from the circuit if not given.
+        #[arg(long)]
+        vk: Option<String>,
+        /// Directory holding the EVM verifier of the release, for EVM proofs. Defaults to the
+        /// `verifier` directory next to the circuit.
+        #[arg(long)]
+        verifier: Option<PathBuf>,
+    },
+    /// Print the baseline, commitments, public values and stats of a proof.
+    Inspect {
+        /// Path to the proof (JSON).
+        proof: PathBuf,
+    },
+    /// Print the app vk of a circuit, or its EVM vk.
+    Vk {
+        #[command(flatten)]
+        circuit: CircuitArgs,
+        /// Print the EVM vk instead. This runs the keygen of the whole circuit.
+        #[arg(long)]
+        evm: bool,
+    },
+}
+
+/// The circuit to load a prover for.
+#[derive(Args)]
+struct CircuitArgs {
+    /// Directory holding the circuit's `app.vmexe` and `openvm.toml`.
+    #[arg(long)]
+    circuit: PathBuf,
+    /// Name of the circuit, e.g. `chunk`. Defaults to the name of the circuit directory.
+    #[arg(long)]
+    name: Option<String>,
+    /// Directory holding the `agg_vk.bin` and `def_hook_commit.json` of the child circuit, for
+    /// aggregation circuits.
+    #[arg(long)]
+    child_dir: Option<PathBuf>,
+    /// Directory to cache the proving keys in.
+    #[arg(long)]
+    dir_keys: Option<PathBuf>,
+}
+
+impl CircuitArgs {
+    fn load(&self) -> eyre::Result<Prover> {
+        let config = ProverConfig {
+            path_app_exe: self.circuit.join("app.vmexe"),
+            path_app_config: self.circuit.join("openvm.toml"),
+            dir_keys: self.dir_keys.clone(),
+            ..Default::default()
+        };
+        let name = self.name.clone().or_else(|| {
+            self.circuit
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        });
+        let mut prover = Prover::setup(config, name.as_deref())?;
+        if let Some(child_dir) = &self.child_dir {
+            prover.enable_deferral_from_assets(child_dir)?;
+        }
+        Ok(prover)
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    init_tracing();
+
+    match Cli::parse().command {
+        Command::Execute { circuit, task } => {
+            let prover = circuit.load()?;
+            let task: ProvingTask = read_json_deep(&task)?;
+            let report = if task.aggregated_proofs.is_empty() {
+                prover.dry_run(&task)?
+            } else {
+                prover.dry_run_aggregation(&task, &task.aggregated_proofs)?
+            };
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        Command::Replay { bundle } => {
+            let info: crash::CrashInfo = read_json_deep(&bundle.join(crash::FD_CRASH_INFO))?;
+            let path_stdin = bundle.join(crash::FD_STDIN_BIN);
+            let stdin: StdIn = openvm_sdk::fs::read_object_from_file(&path_stdin)
+                .map_err(|e| eyre::eyre!("failed to read {}: {e}", path_stdin.display()))?;
+            let config = ProverConfig {
+                path_app_exe: info.path_app_exe,
+                path_app_config: info.path_app_config,
+                release: Some(info.guest_release).filter(|release| !release.is_empty()),
+                ..Default::default()
+            };
+            let prover = Prover::setup(config, Some(&info.prover_name))?;
+            let total_cycles = prover.execute_and_check(&stdin)?;
+            println!("{}", json!({ "total_cycles": total_cycles }));
+        }
+        Command::Prove {
+            circuit,
+            task,
+            snark,
+            out,
+        } => {
+            let prover = circuit.load()?;
+            let task: ProvingTask = read_json_deep(&task)?;
+            let proof = if task.aggregated_proofs.is_empty() {
+                prover.gen_proof_universal(&task, snark)?
+            } else {
+                prover.prove_aggregation(&task, &task.aggregated_proofs, snark)?
+            };
+            write_json(&out, &proof)?;
+            println!(
+                "proof of task {} written to {}",
+                task.identifier,
+                out.display()
+            );
+        }
+        Command::Verify {
+            circuit,
+            proof,
+            vk,
+            verifier,
+        } => {
+            let proof: ProofEnum = read_json_deep(&proof)?;
+            let vk = match vk {
+                Some(vk) => hex::decode(vk.trim_start_matches("0x"))?,
+                None => compute_app_vk(circuit.join("app.vmexe"), circuit.join("openvm.toml"))?,
+            };
+            match &proof {
+                ProofEnum::Stark(stark_proof) => {
+                    let path_agg_vk = circuit.join("agg_vk.bin");
+                    let agg_vk: MultiStarkVerifyingKey<SC> =
+                        openvm_sdk::fs::read_object_from_file(&path_agg_vk).map_err(|e| {
+                            eyre::eyre!("failed to read {}: {e}", path_agg_vk.display())
+                        })?;
+                    UniversalVerifier::verify_stark_proof_strict_with_vk(
+                        &agg_vk,
+                        stark_proof,
+                        &vk,
+                        read_def_hook_commit(&circuit)?,
+                    )?;
+                    println!("valid STARK proof, vk {}", hex::encode(&vk));
+                }
+                ProofEnum::Evm(evm_proof) => {
+                    let dir_verifier = verifier.unwrap_or_else(|| {
+                        circuit.parent().unwrap_or(Path::new(".")).join("verifier")
+                    });
+                    let verifier = UniversalVerifier::setup(dir_verifier)?;
+                    let gas = verifier.verify_evm_proof(&evm_proof.clone().into(), &vk)?;
+                    println!("valid EVM proof, vk {}, gas {gas}", hex::encode(&vk));
+                }
+            }
+        }
+        Command::Inspect { proof } => {
+            let proof: ProofEnum = read_json_deep(&proof)?;
+            println!("{}", serde_json::to_string_pretty(&inspect(&proof)?)?);
+        }
+        Command::Vk { circuit, evm } => {
+            if evm {
+                let prover = circuit.load()?;
+                println!("{}", hex::encode(prover.get_evm_vk()?));
+            } else {
+                let vk = compute_app_vk(
+                    circuit.circuit.join("app.vmexe"),
+                    circuit.circuit.join("openvm.toml"),
+                )?;
+                println!("{}", hex::encode(vk));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Summary of `proof`: its baseline, commitments, public values and stats.
+fn inspect(proof: &ProofEnum) -> eyre::Result<serde_json::Value> {
+    let public_values = proof.public_values();
+    // Values that are not bytes are shown as is rather than truncated.
+    let (pi_hash, public_values) = match public_values_as_bytes(&public_values) {
+        Some(bytes) => (bytes.get(..32).map(hex::encode), json!(hex::encode(&bytes))),
+        None => (None, json!(public_values)),
+    };
+
+    let summary = match proof {
+        ProofEnum::Stark(stark_proof) => {
+            let (_, baseline) = decode_stark_proof(stark_proof)?;
+            let commitment = ProgramCommitment {
+                exe: baseline.app_exe_commit.map(|f| f.as_canonical_u32()),
+                vm: baseline.app_vk_commit.map(|f| f.as_canonical_u32()),
+            };
+            json!({
+                "kind": "stark",
+                "app_vk": hex::encode(serialize_vk::serialize(&commitment)),
+                "commitments": commitment,
+                "baseline": baseline,
+                "pi_hash": pi_hash,
+                "public_values": public_values,
+                "stat": stark_proof.stat,
+            })
+        }
+        ProofEnum::Evm(evm_proof) => {
+            let app_commit = OpenVmEvmProof::from(evm_proof.clone()).app_commit;
+            let exe: [openvm_sdk::F; 8] = app_commit.app_exe_commit.into();
+            let vm: [openvm_sdk::F; 8] = app_commit.app_vm_commit.into();
+            let commitment = ProgramCommitment {
+                exe: exe.map(|f| f.as_canonical_u32()),
+                vm: vm.map(|f| f.as_canonical_u32()),
+            };
+            json!({
+                "kind": "evm",
+                "app_vk": hex::encode(serialize_vk::serialize(&commitment)),
+                "commitments": commitment,
+                "pi_hash": pi_hash,
+                "public_values": public_values,
+                "stat": evm_proof.stat,
+            })
+        }
+    };
+    Ok(summary)
+}
+
+fn init_tracing() {
+    use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    if let Err(e) = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .try_init()
+    {
+        eprintln!("failed to install tracing subscriber: {e}");
+    }
+}
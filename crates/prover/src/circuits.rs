@@ -0,0 +1,99 @@
+//! Loading a set of circuits (chunk, batch, bundle) into provers, shared by the daemon and the
+//! coordinator client.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Prover, ProverConfig};
+
+/// File name of the application exe in a circuit directory.
+pub(crate) const FD_APP_EXE: &str = "app.vmexe";
+
+/// File name of the application config in a circuit directory.
+pub(crate) const FD_APP_CONFIG: &str = "openvm.toml";
+
+/// A circuit to load, see [`load_circuits`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CircuitConfig {
+    /// Name of the circuit, e.g. `chunk`, `batch` or `bundle`.
+    pub name: String,
+    /// Directory holding the circuit's `app.vmexe` and `openvm.toml`.
+    pub dir: PathBuf,
+    /// Name of the circuit whose proofs this circuit aggregates, if any.
+    #[serde(default)]
+    pub child: Option<String>,
+    /// Directory holding the `agg_vk.bin` and `def_hook_commit.json` of the child circuit, to
+    /// aggregate its proofs without loading it, see [`Prover::enable_deferral_from_assets`].
+    /// Only used if the child is not loaded.
+    #[serde(default)]
+    pub child_dir: Option<PathBuf>,
+}
+
+/// A loaded circuit.
+pub struct Circuit {
+    /// Name of the circuit.
+    pub name: String,
+    /// The app vk, see [`Prover::get_app_vk`].
+    pub vk: Vec<u8>,
+    /// The prover.
+    pub prover: Prover,
+}
+
+/// Load the provers of all `circuits` of the guest `release`, enabling deferral for aggregation
+/// circuits. The provers only admit tasks of `forks`, see [`Prover::admit`], cache their proving keys in `dir_keys`
+/// and write crash bundles in `dir_crash` if specified, see [`ProverConfig::dir_keys`] and
+/// [`ProverConfig::dir_crash`].
+///
+/// Children must be listed before the circuits aggregating them, unless the assets of the child
+/// are given, see [`CircuitConfig::child_dir`].
+///
+/// The app vks are read from the assets of the release when recorded, so that loading a circuit
+/// does not run its keygen, see [`Prover::get_app_commitment`].
+pub fn load_circuits(
+    circuits: &[CircuitConfig],
+    release: Option<&str>,
+    forks: &[String],
+    dir_cache: Option<&Path>,
+    dir_keys: Option<&Path>,
+    dir_crash: Option<&Path>,
+) -> Result<Vec<Circuit>, Error> {
+    let mut loaded: Vec<Circuit> = Vec::with_capacity(circuits.len());
+    for circuit in circuits {
+        let config = ProverConfig {
+            path_app_exe: circuit.dir.join(FD_APP_EXE),
+            path_app_config: circuit.dir.join(FD_APP_CONFIG),
+            dir_cache: dir_cache.map(|dir| dir.join(&circuit.name)),
+            dir_keys: dir_keys.map(Path::to_path_buf),
+            dir_crash: dir_crash.map(Path::to_path_buf),
+            release: release.map(str::to_string),
+            forks: forks.to_vec(),
+            agg_config: None,
+        };
+        let mut prover = Prover::setup(config, Some(&circuit.name))?;
+        if let Some(child) = &circuit.child {
+            match (
+                loaded.iter().find(|loaded| &loaded.name == child),
+                &circuit.child_dir,
+            ) {
+                (Some(child), _) => prover.enable_deferral(&child.prover)?,
+                (None, Some(child_dir)) => prover.enable_deferral_from_assets(child_dir)?,
+                (None, None) => {
+                    return Err(Error::Custom(format!(
+                        "child circuit {child} of {} must be listed before it, or its assets \
+                         given as child_dir",
+                        circuit.name
+                    )));
+                }
+            }
+        }
+        let vk = prover.get_app_vk();
+        tracing::info!("loaded circuit {}, vk {}", circuit.name, hex::encode(&vk));
+        loaded.push(Circuit {
+            name: circuit.name.clone(),
+            vk,
+            prover,
+        });
+    }
+    Ok(loaded)
+}
@@ -0,0 +1,244 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    AssignedTask, Backoff, Coordinator, CoordinatorError, LoginRequest, Submission, TaskRequest,
+    duration_secs,
+};
+use crate::{
+    Error,
+    circuits::Circuit,
+    progress::{CancellationToken, ProvingControl},
+    utils::short_git_version,
+};
+
+fn default_poll_interval() -> Duration {
+    Duration::from_secs(10)
+}
+
+fn default_heartbeat_interval() -> Duration {
+    Duration::from_secs(30)
+}
+
+/// Configure the [`Client`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClientConfig {
+    /// Name the prover logs in with.
+    pub prover_name: String,
+    /// Delay before requesting a task again when none was available.
+    #[serde(with = "duration_secs", default = "default_poll_interval")]
+    pub poll_interval: Duration,
+    /// Interval of heartbeats sent while proving.
+    #[serde(with = "duration_secs", default = "default_heartbeat_interval")]
+    pub heartbeat_interval: Duration,
+    /// Backoff for retrying failed requests.
+    #[serde(default)]
+    pub backoff: Backoff,
+}
+
+/// Pull loop against a [`Coordinator`].
+pub struct Client {
+    coordinator: Arc<dyn Coordinator>,
+    circuits: Vec<Circuit>,
+    config: ClientConfig,
+    token: Option<String>,
+}
+
+impl Client {
+    /// Client proving tasks for `circuits`.
+    pub fn new(
+        coordinator: Arc<dyn Coordinator>,
+        circuits: Vec<Circuit>,
+        config: ClientConfig,
+    ) -> Self {
+        Self {
+            coordinator,
+            circuits,
+            config,
+            token: None,
+        }
+    }
+
+    /// The loaded circuits.
+    pub fn circuits(&self) -> &[Circuit] {
+        &self.circuits
+    }
+
+    fn vks(&self) -> Vec<String> {
+        self.circuits
+            .iter()
+            .map(|circuit| hex::encode(&circuit.vk))
+            .collect()
+    }
+
+    /// Call `f` with a valid session token, logging in (again) as needed and retrying with
+    /// backoff.
+    fn call<T>(
+        &mut self,
+        what: &str,
+        mut f: impl FnMut(&dyn Coordinator, &str) -> Result<T, CoordinatorError>,
+    ) -> Result<T, CoordinatorError> {
+        let coordinator = self.coordinator.clone();
+        let backoff = self.config.backoff.clone();
+        let login = LoginRequest {
+            prover_name: self.config.prover_name.clone(),
+            prover_version: short_git_version(),
+            vks: self.vks(),
+        };
+        let token = &mut self.token;
+        backoff.retry(what, || {
+            let session = match token.clone() {
+                Some(session) => session,
+                None => {
+                    tracing::info!("logging in as {}", login.prover_name);
+                    token.insert(coordinator.login(&login)?).clone()
+                }
+            };
+            let result = f(coordinator.as_ref(), &session);
+            if let Err(CoordinatorError::Unauthorized(_)) = &result {
+                *token = None;
+            }
+            result
+        })
+    }
+
+    /// Request a task and, if one was assigned, prove it and submit the outcome.
+    ///
+    /// Returns whether a task was assigned.
+    pub fn run_once(&mut self) -> Result<bool, CoordinatorError> {
+        let request = TaskRequest {
+            circuits: self.circuits.iter().map(|c| c.name.clone()).collect(),
+            vks: self.vks(),
+        };
+        let Some(mut assigned) = self.call("get_task", |c, token| c.get_task(token, &request))?
+        else {
+            return Ok(false);
+        };
+        let task_id = assigned.task_id.clone();
+        tracing::info!("assigned task {task_id} for {}", assigned.circuit);
+
+        if !assigned.child_task_ids.is_empty() && assigned.task.aggregated_proofs.is_empty() {
+            let child_task_ids = assigned.child_task_ids.clone();
+            assigned.task.aggregated_proofs = self.call("get_proofs", |c, token| {
+                c.get_proofs(token, &child_task_ids)
+            })?;
+        }
+
+        let submission = match self.prove(&assigned) {
+            Ok(proof) => Submission {
+                task_id: task_id.clone(),
+                proof: Some(proof),
+                failure: None,
+                retryable: false,
+            },
+            Err(Error::Cancelled) => {
+                tracing::info!("task {task_id} is no longer assigned, dropped");
+                return Ok(true);
+            }
+            Err(e) => {
+                tracing::warn!("failed to prove task {task_id}: {e}");
+                Submission {
+                    task_id: task_id.clone(),
+                    proof: None,
+                    failure: Some(e.to_string()),
+                    retryable: e.is_retryable(),
+                }
+            }
+        };
+        self.call("submit_proof", |c, token| c.submit(token, &submission))?;
+        tracing::info!("submitted task {task_id}");
+        Ok(true)
+    }
+
+    /// Prove `assigned`, sending heartbeats meanwhile. The proof is cancelled if the
+    /// coordinator reports that the task is no longer assigned.
+    fn prove(
+        &mut self,
+        assigned: &AssignedTask,
+    ) -> Result<scroll_zkvm_types::proof::ProofEnum, Error> {
+        let token = self.token.clone().unwrap_or_default();
+        let coordinator = self.coordinator.clone();
+        let heartbeat_interval = self.config.heartbeat_interval;
+        let circuit = self
+            .circuits
+            .iter_mut()
+            .find(|circuit| circuit.name == assigned.circuit)
+            .ok_or_else(|| Error::Custom(format!("circuit {} not loaded", assigned.circuit)))?;
+
+        let cancel = CancellationToken::new();
+        let done = AtomicBool::new(false);
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                let mut last = Instant::now();
+                while !done.load(Ordering::SeqCst) {
+                    std::thread::sleep(Duration::from_millis(200));
+                    if last.elapsed() < heartbeat_interval {
+                        continue;
+                    }
+                    last = Instant::now();
+                    match coordinator.heartbeat(&token, &assigned.task_id) {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            tracing::info!("task {} was reassigned, cancelling", assigned.task_id);
+                            cancel.cancel();
+                            break;
+                        }
+                        // A missed heartbeat is not fatal, the next one may get through.
+                        Err(e) => tracing::warn!("heartbeat failed: {e}"),
+                    }
+                }
+            });
+
+            // Stop the heartbeats also if proving panics, the scope would never end otherwise.
+            struct Done<'a>(&'a AtomicBool);
+            impl Drop for Done<'_> {
+                fn drop(&mut self) {
+                    self.0.store(true, Ordering::SeqCst);
+                }
+            }
+            let _done = Done(&done);
+
+            let control = ProvingControl::new(cancel.clone());
+            let task = &assigned.task;
+            if task.aggregated_proofs.is_empty() {
+                circuit
+                    .prover
+                    .gen_proof_universal_with_control(task, assigned.with_snark, &control)
+            } else {
+                circuit.prover.prove_aggregation_with_control(
+                    task,
+                    &task.aggregated_proofs,
+                    assigned.with_snark,
+                    &control,
+                )
+            }
+        })
+    }
+
+    /// Run the pull loop until `stop` is cancelled.
+    pub fn run(&mut self, stop: &CancellationToken) {
+        let mut failures = 0;
+        while !stop.is_cancelled() {
+            match self.run_once() {
+                Ok(true) => failures = 0,
+                Ok(false) => {
+                    failures = 0;
+                    std::thread::sleep(self.config.poll_interval);
+                }
+                Err(e) => {
+                    let delay = self.config.backoff.delay(failures);
+                    tracing::error!("pull loop failed ({e}), retrying in {delay:?}");
+                    std::thread::sleep(delay);
+                    failures = failures.saturating_add(1);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use std::time::Duration;
+
+use scroll_zkvm_types::proof::StarkProof;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use super::{AssignedTask, Coordinator, CoordinatorError, LoginRequest, Submission, TaskRequest};
+
+/// Error code of the coordinator for a missing or expired token.
+const ERR_CODE_UNAUTHORIZED: i64 = 401;
+
+/// Envelope of every response of the coordinator.
+#[derive(Deserialize)]
+struct Envelope<T> {
+    errcode: i64,
+    #[serde(default)]
+    errmsg: String,
+    data: Option<T>,
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    token: String,
+}
+
+#[derive(Serialize)]
+struct GetProofsRequest<'a> {
+    task_ids: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct GetProofsResponse {
+    proofs: Vec<StarkProof>,
+}
+
+#[derive(Serialize)]
+struct HeartbeatRequest<'a> {
+    task_id: &'a str,
+}
+
+#[derive(Deserialize)]
+struct HeartbeatResponse {
+    assigned: bool,
+}
+
+/// [`Coordinator`] reached over HTTP.
+///
+/// Every endpoint takes a JSON body via `POST` and responds with a JSON envelope
+/// `{"errcode": 0, "errmsg": "", "data": ..}`. The session token is passed as bearer token.
+///
+/// - `{base_url}/v1/login`
+/// - `{base_url}/v1/get_task`
+/// - `{base_url}/v1/get_proofs`
+/// - `{base_url}/v1/heartbeat`
+/// - `{base_url}/v1/submit_proof`
+pub struct HttpCoordinator {
+    base_url: String,
+    agent: ureq::Agent,
+}
+
+impl HttpCoordinator {
+    /// Coordinator at `base_url`, with the given timeout for each request.
+    pub fn new(base_url: impl Into<String>, timeout: Duration) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            agent: ureq::AgentBuilder::new().timeout(timeout).build(),
+        }
+    }
+
+    fn post<B: Serialize, T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        token: Option<&str>,
+        body: &B,
+    ) -> Result<Option<T>, CoordinatorError> {
+        let mut request = self.agent.post(&format!("{}/v1/{endpoint}", self.base_url));
+        if let Some(token) = token {
+            request = request.set("Authorization", &format!("Bearer {token}"));
+        }
+        let response = match request.send_json(body) {
+            Ok(response) => response,
+            Err(ureq::Error::Status(401, response)) => {
+                return Err(CoordinatorError::Unauthorized(
+                    response.into_string().unwrap_or_default(),
+                ));
+            }
+            Err(ureq::Error::Status(code, response)) if code >= 500 => {
+                return Err(CoordinatorError::Transport(format!(
+                    "{endpoint}: status {code}: {}",
+                    response.into_string().unwrap_or_default()
+                )));
+            }
+            Err(ureq::Error::Status(code, response)) => {
+                return Err(CoordinatorError::Api {
+                    code: code.into(),
+                    message: response.into_string().unwrap_or_default(),
+                });
+            }
+            Err(e) => return Err(CoordinatorError::Transport(format!("{endpoint}: {e}"))),
+        };
+        let envelope: Envelope<T> = response
+            .into_json()
+            .map_err(|e| CoordinatorError::Transport(format!("{endpoint}: {e}")))?;
+        match envelope.errcode {
+            0 => Ok(envelope.data),
+            ERR_CODE_UNAUTHORIZED => Err(CoordinatorError::Unauthorized(envelope.errmsg)),
+            code => Err(CoordinatorError::Api {
+                code,
+                message: envelope.errmsg,
+            }),
+        }
+    }
+}
+
+impl Coordinator for HttpCoordinator {
+    fn login(&self, request: &LoginRequest) -> Result<String, CoordinatorError> {
+        let response: Option<LoginResponse> = self.post("login", None, request)?;
+        response
+            .map(|response| response.token)
+            .ok_or_else(|| CoordinatorError::Transport("login: missing token".to_string()))
+    }
+
+    fn get_task(
+        &self,
+        token: &str,
+        request: &TaskRequest,
+    ) -> Result<Option<AssignedTask>, CoordinatorError> {
+        self.post("get_task", Some(token), request)
+    }
+
+    fn get_proofs(
+        &self,
+        token: &str,
+        task_ids: &[String],
+    ) -> Result<Vec<StarkProof>, CoordinatorError> {
+        let response: Option<GetProofsResponse> =
+            self.post("get_proofs", Some(token), &GetProofsRequest { task_ids })?;
+        Ok(response.map(|response| response.proofs).unwrap_or_default())
+    }
+
+    fn heartbeat(&self, token: &str, task_id: &str) -> Result<bool, CoordinatorError> {
+        let response: Option<HeartbeatResponse> =
+            self.post("heartbeat", Some(token), &HeartbeatRequest { task_id })?;
+        Ok(response.is_none_or(|response| response.assigned))
+    }
+
+    fn submit(&self, token: &str, submission: &Submission) -> Result<(), CoordinatorError> {
+        let _: Option<serde_json::Value> = self.post("submit_proof", Some(token), submission)?;
+        Ok(())
+    }
+}
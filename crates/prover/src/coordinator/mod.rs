@@ -0,0 +1,197 @@
+//! Client for the pull model of a coordinator: log in, request a task, fetch the child proofs,
+//! prove and submit the proof.
+//!
+//! The transport is abstracted by the [`Coordinator`] trait. [`HttpCoordinator`] talks to a
+//! coordinator over HTTP, while tests can plug in an in-process implementation.
+
+use std::time::Duration;
+
+use scroll_zkvm_types::{
+    proof::{ProofEnum, StarkProof},
+    task::ProvingTask,
+};
+use serde::{Deserialize, Serialize};
+
+mod client;
+pub use client::{Client, ClientConfig};
+
+mod http;
+pub use http::HttpCoordinator;
+
+/// Errors encountered while talking to a coordinator.
+#[derive(thiserror::Error, Debug)]
+pub enum CoordinatorError {
+    /// The session token is missing, invalid or expired. Logging in again may help.
+    #[error("unauthorized: {0}")]
+    Unauthorized(String),
+    /// The coordinator could not be reached.
+    #[error("transport error: {0}")]
+    Transport(String),
+    /// The coordinator rejected the request.
+    #[error("coordinator error {code}: {message}")]
+    Api {
+        /// Error code returned by the coordinator.
+        code: i64,
+        /// Error message returned by the coordinator.
+        message: String,
+    },
+}
+
+impl CoordinatorError {
+    /// Whether retrying the request, after some backoff, may succeed.
+    pub fn is_retryable(&self) -> bool {
+        !matches!(self, Self::Api { .. })
+    }
+}
+
+/// Login request of a prover.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LoginRequest {
+    /// Name of the prover.
+    pub prover_name: String,
+    /// Version of the prover, see [`short_git_version`][crate::utils::short_git_version].
+    pub prover_version: String,
+    /// Hex-encoded app vks of the circuits the prover can prove.
+    pub vks: Vec<String>,
+}
+
+/// Request for a new task.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TaskRequest {
+    /// Names of the circuits the prover accepts tasks for.
+    pub circuits: Vec<String>,
+    /// Hex-encoded app vks of those circuits.
+    pub vks: Vec<String>,
+}
+
+/// A task assigned by the coordinator.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AssignedTask {
+    /// Identifier of the task, used to fetch child proofs, send heartbeats and submit the proof.
+    pub task_id: String,
+    /// Name of the circuit to prove the task with.
+    pub circuit: String,
+    /// Whether the proof should be wrapped into an EVM proof.
+    #[serde(default)]
+    pub with_snark: bool,
+    /// Identifiers of the tasks whose proofs are aggregated by this task. Their proofs are
+    /// fetched into [`ProvingTask::aggregated_proofs`] before proving.
+    #[serde(default)]
+    pub child_task_ids: Vec<String>,
+    /// The task to prove.
+    pub task: ProvingTask,
+}
+
+/// The outcome of a task, as submitted to the coordinator.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Submission {
+    /// Identifier of the task.
+    pub task_id: String,
+    /// The proof, if proving succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof: Option<ProofEnum>,
+    /// The failure, if proving failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure: Option<String>,
+    /// Whether proving the task again may succeed, see
+    /// [`Error::is_retryable`][crate::Error::is_retryable].
+    #[serde(default)]
+    pub retryable: bool,
+}
+
+/// The API of a coordinator, as used by [`Client`].
+pub trait Coordinator: Send + Sync {
+    /// Log in, returning the session token used by the other calls.
+    fn login(&self, request: &LoginRequest) -> Result<String, CoordinatorError>;
+
+    /// Request a task, returning `None` if there is none available.
+    fn get_task(
+        &self,
+        token: &str,
+        request: &TaskRequest,
+    ) -> Result<Option<AssignedTask>, CoordinatorError>;
+
+    /// Fetch the proofs of the given tasks, in the same order.
+    fn get_proofs(
+        &self,
+        token: &str,
+        task_ids: &[String],
+    ) -> Result<Vec<StarkProof>, CoordinatorError>;
+
+    /// Report that the task is still being proven. Returns `false` if the task is no longer
+    /// assigned to this prover, in which case proving should be aborted.
+    fn heartbeat(&self, token: &str, task_id: &str) -> Result<bool, CoordinatorError>;
+
+    /// Submit the outcome of a task.
+    fn submit(&self, token: &str, submission: &Submission) -> Result<(), CoordinatorError>;
+}
+
+/// Exponential backoff for retrying requests to the coordinator.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Backoff {
+    /// Delay before the first retry.
+    #[serde(with = "duration_secs")]
+    pub initial: Duration,
+    /// Upper bound of the delay between retries.
+    #[serde(with = "duration_secs")]
+    pub max: Duration,
+    /// Number of retries before giving up.
+    pub max_retries: u32,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(60),
+            max_retries: 8,
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay before the retry number `attempt` (0-based).
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.initial
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max)
+    }
+
+    /// Run `f` until it succeeds, fails with an error that is not retryable, or the retries are
+    /// exhausted.
+    pub fn retry<T>(
+        &self,
+        what: &str,
+        mut f: impl FnMut() -> Result<T, CoordinatorError>,
+    ) -> Result<T, CoordinatorError> {
+        let mut attempt = 0;
+        loop {
+            match f() {
+                Ok(value) => return Ok(value),
+                Err(e) if e.is_retryable() && attempt < self.max_retries => {
+                    let delay = self.delay(attempt);
+                    tracing::warn!("{what} failed ({e}), retrying in {delay:?}");
+                    std::thread::sleep(delay);
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Serde of [`Duration`] as (fractional) seconds.
+pub(crate) mod duration_secs {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(d: &Duration, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_f64(d.as_secs_f64())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Duration, D::Error> {
+        let secs = f64::deserialize(d)?;
+        Duration::try_from_secs_f64(secs).map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,171 @@
+//! Crash bundles, written by a [`Prover`][crate::Prover] when executing or proving a task fails,
+//! see [`ProverConfig::dir_crash`][crate::ProverConfig::dir_crash].
+//!
+//! A bundle is a directory holding what is needed to replay the failure offline:
+//!
+//! - `task.json`: the task,
+//! - `stdin.json`: the guest input, in the format of `cargo openvm run --input`,
+//! - `stdin.bin`: the whole guest input, including the deferral states of aggregation tasks,
+//! - `def_inputs.bin`: the inputs of the deferral prover, for aggregation tasks only,
+//! - `guest_stdout.txt`: what the guest printed in the run that failed,
+//! - `crash.json`: the error and the circuit, release and prover version, see [`CrashInfo`],
+//! - `replay.sh`: the command executing the guest with the same input.
+//!
+//! `cargo openvm run` does not take deferral states, so the bundles of aggregation tasks are
+//! replayed by `zkvm replay` instead, which executes `stdin.bin`.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use openvm_sdk::{DeferralInput, StdIn, fs::write_object_to_file};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Error,
+    utils::{save_stdin_as_json, write, write_json},
+};
+
+/// File name of the [`CrashInfo`] of a bundle.
+pub const FD_CRASH_INFO: &str = "crash.json";
+
+/// File name of the task of a bundle.
+pub const FD_TASK: &str = "task.json";
+
+/// File name of the guest input of a bundle.
+pub const FD_STDIN: &str = "stdin.json";
+
+/// File name of the whole guest input of a bundle, bincode encoded.
+pub const FD_STDIN_BIN: &str = "stdin.bin";
+
+/// File name of the deferral prover inputs of a bundle, bincode encoded.
+pub const FD_DEF_INPUTS: &str = "def_inputs.bin";
+
+/// File name of the captured guest output of a bundle.
+pub const FD_GUEST_STDOUT: &str = "guest_stdout.txt";
+
+/// File name of the replay script of a bundle.
+pub const FD_REPLAY: &str = "replay.sh";
+
+/// What failed, and with which circuit, recorded in a crash bundle.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CrashInfo {
+    /// Name of the prover, e.g. `chunk`.
+    pub prover_name: String,
+    /// Identifier of the task.
+    pub task_id: String,
+    /// Fork of the task.
+    pub fork_name: String,
+    /// The error the task failed with.
+    pub error: String,
+    /// Hex-encoded app vk of the circuit, empty if it could not be derived.
+    pub app_vk: String,
+    /// Path of the circuit's `app.vmexe`.
+    pub path_app_exe: PathBuf,
+    /// Path of the circuit's `openvm.toml`.
+    pub path_app_config: PathBuf,
+    /// Git version of the prover, see [`GIT_VERSION`][crate::utils::GIT_VERSION].
+    pub prover_version: String,
+    /// Release of the guest, see [`ProverConfig::release`][crate::ProverConfig::release], empty
+    /// if the prover was not set up from a release.
+    #[serde(default)]
+    pub guest_release: String,
+    /// Unix timestamp of the failure, in seconds.
+    pub timestamp: u64,
+}
+
+/// Number of bundles written by this process, keeping their directories apart.
+static NUM_BUNDLES: AtomicU64 = AtomicU64::new(0);
+
+/// Write a crash bundle for `task`, failing as described by `info`, in a new directory of
+/// `dir_crash`. Returns the directory of the bundle.
+///
+/// The `def_inputs` are those of the deferral prover, empty unless the task aggregates child
+/// proofs. The `guest_output` is what the guest printed in the run that failed.
+pub fn write_bundle<T: Serialize>(
+    dir_crash: &Path,
+    info: &CrashInfo,
+    task: &T,
+    stdin: &StdIn,
+    def_inputs: &[DeferralInput],
+    guest_output: &str,
+) -> Result<PathBuf, Error> {
+    // The same task may fail several times within a second, possibly in several processes.
+    let dir = dir_crash.join(format!(
+        "{}-{}-{}-{}-{}",
+        sanitize(&info.prover_name),
+        sanitize(&info.task_id),
+        info.timestamp,
+        std::process::id(),
+        NUM_BUNDLES.fetch_add(1, Ordering::Relaxed),
+    ));
+    std::fs::create_dir_all(&dir).map_err(|source| Error::IoReadWrite {
+        path: dir.clone(),
+        source,
+    })?;
+
+    write_json(dir.join(FD_CRASH_INFO), info)?;
+    write_json(dir.join(FD_TASK), task)?;
+    save_stdin_as_json(stdin, &dir.join(FD_STDIN).to_string_lossy());
+    write_object(&dir.join(FD_STDIN_BIN), stdin)?;
+    if !def_inputs.is_empty() {
+        write_object(&dir.join(FD_DEF_INPUTS), &def_inputs)?;
+    }
+    write(dir.join(FD_GUEST_STDOUT), guest_output.as_bytes())?;
+    write_replay_script(&dir, info, !stdin.deferrals.is_empty())?;
+
+    Ok(dir)
+}
+
+/// Write `object` to `path`, bincode encoded.
+fn write_object<T: Serialize>(path: &Path, object: &T) -> Result<(), Error> {
+    write_object_to_file(path, object)
+        .map_err(|e| Error::Custom(format!("failed to write {}: {e}", path.display())))
+}
+
+/// Write the script replaying the execution of the bundle in `dir`. With `deferrals`, the
+/// guest reads deferral states `cargo openvm run` cannot provide, so `zkvm replay` runs it.
+fn write_replay_script(dir: &Path, info: &CrashInfo, deferrals: bool) -> Result<(), Error> {
+    let absolute = |path: &Path| std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let command = if deferrals {
+        "zkvm replay \"$(dirname \"$0\")\"".to_string()
+    } else {
+        format!(
+            "cargo openvm run --exe '{}' --config '{}' --input \"$(dirname \"$0\")/{FD_STDIN}\"",
+            absolute(&info.path_app_exe).display(),
+            absolute(&info.path_app_config).display(),
+        )
+    };
+    let script = format!(
+        "#!/bin/sh\n\
+         # Execute the {} guest with the input of task {}, which failed with:\n\
+         # {}\n\
+         {command}\n",
+        info.prover_name,
+        info.task_id,
+        info.error.lines().next().unwrap_or_default(),
+    );
+    let path = dir.join(FD_REPLAY);
+    write(&path, script.as_bytes())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+            .map_err(|source| Error::IoReadWrite { path, source })?;
+    }
+    Ok(())
+}
+
+/// Keep `s` usable as part of a file name.
+fn sanitize(s: &str) -> String {
+    s.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
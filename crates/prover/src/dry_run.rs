@@ -0,0 +1,114 @@
+//! Execution-only validation of a task, without proving it.
+//!
+//! A dry run executes the guest program the same way the prover does, reporting the segmentation
+//! and the public-input hash revealed by the guest. For chunk tasks, the [`ChunkInfo`] is
+//! additionally computed natively on the host and its hash compared against the guest's.
+
+use serde::Serialize;
+
+#[cfg(feature = "scroll")]
+use scroll_zkvm_types::{
+    public_inputs::{MultiVersionPublicInputs, Version},
+    scroll::chunk::{ChunkInfo, ChunkWitness},
+    task::ProvingTask as UniversalProvingTask,
+};
+
+#[cfg(feature = "scroll")]
+use crate::AdmissionError;
+use crate::Error;
+
+/// Report of a dry run, see [`Prover::dry_run`][crate::Prover::dry_run].
+#[derive(Clone, Debug, Serialize)]
+pub struct DryRunReport {
+    /// Number of segments the execution is split into, i.e. the number of app proofs.
+    pub num_segments: usize,
+    /// Number of instructions executed in each segment.
+    pub segment_cycles: Vec<u64>,
+    /// Total number of instructions executed.
+    pub total_cycles: u64,
+    /// Wall time of the execution.
+    pub execution_time_mills: u64,
+    /// The public-input hash revealed by the guest.
+    #[serde(with = "hex_bytes32")]
+    pub pi_hash: [u8; 32],
+    /// The chunk public inputs, for chunk tasks, see [`Prover::dry_run_chunk`][crate::Prover::dry_run_chunk].
+    #[cfg(feature = "scroll")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk: Option<ChunkCheck>,
+}
+
+impl DryRunReport {
+    /// Whether the task is expected to be provable, i.e. the host-side checks (if any) passed.
+    pub fn is_consistent(&self) -> bool {
+        #[cfg(feature = "scroll")]
+        return self
+            .chunk
+            .as_ref()
+            .is_none_or(|chunk| chunk.pi_hash_matches);
+        #[cfg(not(feature = "scroll"))]
+        true
+    }
+}
+
+/// Public inputs of a chunk computed natively on the host.
+#[cfg(feature = "scroll")]
+#[derive(Clone, Debug, Serialize)]
+pub struct ChunkCheck {
+    /// The version of the witness.
+    pub version: Version,
+    /// The chunk info, as computed by executing the witness on the host.
+    pub chunk_info: ChunkInfo,
+    /// `pi_hash_by_version` of the chunk info.
+    #[serde(with = "hex_bytes32")]
+    pub expected_pi_hash: [u8; 32],
+    /// Whether [`ChunkCheck::expected_pi_hash`] equals the pi_hash revealed by the guest.
+    pub pi_hash_matches: bool,
+}
+
+#[cfg(feature = "scroll")]
+impl ChunkCheck {
+    /// Compute the [`ChunkInfo`] of the chunk task natively and compare its hash against
+    /// `guest_pi_hash`.
+    pub fn new(task: &UniversalProvingTask, guest_pi_hash: &[u8; 32]) -> Result<Self, Error> {
+        let witness_bytes = task.serialized_witness.first().ok_or_else(|| {
+            Error::WitnessDeserialization("task carries no serialized witness".to_string())
+        })?;
+        let (witness, _): (ChunkWitness, _) =
+            bincode::serde::decode_from_slice(witness_bytes, bincode::config::standard())
+                .map_err(|e| Error::WitnessDeserialization(format!("chunk witness: {e}")))?;
+
+        let version = Version::try_from_version_byte(witness.version)
+            .ok_or(AdmissionError::UnknownVersion(witness.version))?;
+        let chunk_info = ChunkInfo::try_from(witness)
+            .map_err(|e| Error::Custom(format!("native chunk execution failed: {e}")))?;
+        let expected_pi_hash = chunk_info.pi_hash_by_version(version).0;
+
+        Ok(Self {
+            version,
+            chunk_info,
+            expected_pi_hash,
+            pi_hash_matches: &expected_pi_hash == guest_pi_hash,
+        })
+    }
+}
+
+/// The pi_hash revealed in the first 32 bytes of the guest's public values.
+pub(crate) fn pi_hash_from_public_values(public_values: &[u8]) -> Result<[u8; 32], Error> {
+    public_values
+        .get(..32)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| Error::GuestPanic {
+            message: format!(
+                "guest revealed {} bytes of public values, expected at least 32",
+                public_values.len()
+            ),
+        })
+}
+
+mod hex_bytes32 {
+    use serde::Serializer;
+
+    pub fn serialize<S: Serializer>(bytes: &[u8; 32], s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&format!("0x{}", hex::encode(bytes)))
+    }
+}
@@ -1,5 +1,8 @@
 use std::path::PathBuf;
 
+use openvm_circuit::arch::{ExecutionError, GenerationError};
+use openvm_sdk::SdkError;
+
 /// Errors encountered by the prover.
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -40,7 +43,302 @@ pub enum Error {
     /// An error encountered during proof verification.
     #[error("failed to verify proof: {0}")]
     VerifyProof(String),
+    /// The guest program panicked or otherwise failed during execution.
+    #[error("guest execution failed: {message}")]
+    GuestPanic {
+        /// The message of the guest, or of the executor if the guest's is not available.
+        message: String,
+    },
+    /// The guest program could not deserialize the witness.
+    #[error("failed to deserialize witness: {0}")]
+    WitnessDeserialization(String),
+    /// The execution does not fit the capacity of the VM, e.g. too many segments or a trace
+    /// height exceeding the maximum.
+    #[error("capacity exceeded: {0}")]
+    CapacityExceeded(String),
+    /// A freshly generated proof did not pass verification.
+    #[error("proof failed self-verification: {0}")]
+    SelfVerification(String),
+    /// The host ran out of (GPU) memory or another resource.
+    #[error("resource exhausted: {0}")]
+    ResourceExhausted(String),
+    /// The task was rejected before proving, see [`Prover::admit`][crate::Prover::admit].
+    #[error("task rejected: {0}")]
+    Admission(#[from] AdmissionError),
+    /// The proof was cancelled through its [`CancellationToken`][crate::progress::CancellationToken].
+    #[error("proof generation cancelled")]
+    Cancelled,
     /// A custom error not covered by above variants.
     #[error("custom error: {0}")]
     Custom(String),
 }
+
+impl Error {
+    /// Whether retrying the same task may succeed.
+    ///
+    /// Failures determined by the task itself (a guest panic, a malformed witness, an execution
+    /// exceeding the VM capacity) or by the setup are not retryable. Failures depending on the
+    /// host or the current attempt are.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Io(_)
+            | Self::IoReadWrite { .. }
+            | Self::GenProof(_)
+            | Self::SelfVerification(_)
+            | Self::ResourceExhausted(_)
+            | Self::Cancelled => true,
+            Self::Serde(_)
+            | Self::JsonReadWrite { .. }
+            | Self::Setup { .. }
+            | Self::Commit(_)
+            | Self::Keygen(_)
+            | Self::VerifyProof(_)
+            | Self::GuestPanic { .. }
+            | Self::WitnessDeserialization(_)
+            | Self::CapacityExceeded(_)
+            | Self::Admission(_)
+            | Self::Custom(_) => false,
+        }
+    }
+
+    /// Classify an error reported by the OpenVM SDK while executing or proving, by the executor
+    /// or trace generation error causing it.
+    pub fn from_sdk(e: SdkError) -> Self {
+        Self::from_guest_run(e, "")
+    }
+
+    /// Same as [`Error::from_sdk`] for an error of running the guest program, which printed
+    /// `guest_output` before failing, see [`GuestOutput`][crate::utils::vm::GuestOutput].
+    ///
+    /// A guest failure carries the last lines of the guest output, i.e. the guest's panic
+    /// message, along with the error of the executor.
+    pub fn from_guest_run(e: SdkError, guest_output: &str) -> Self {
+        let mut cause: Option<&(dyn std::error::Error + 'static)> = Some(&e);
+        while let Some(err) = cause {
+            if err.is::<ExecutionError>() {
+                return Self::guest_failure(e, guest_output);
+            }
+            if let Some(GenerationError::TraceHeightsLimitExceeded { .. }) = err.downcast_ref() {
+                return Self::CapacityExceeded(e.to_string());
+            }
+            cause = err.source();
+        }
+        Self::GenProof(e.to_string())
+    }
+
+    fn guest_failure(e: impl std::fmt::Display, guest_output: &str) -> Self {
+        let tail = guest_output_tail(guest_output);
+        let message = if tail.is_empty() {
+            e.to_string()
+        } else {
+            format!("{tail} ({e})")
+        };
+        if GUEST_WITNESS_DESERIALIZATION
+            .iter()
+            .any(|needle| tail.contains(needle))
+        {
+            Self::WitnessDeserialization(message)
+        } else {
+            Self::GuestPanic { message }
+        }
+    }
+
+    /// Classify the payload of a panic raised while executing or proving.
+    ///
+    /// Panics are untyped, so the only failure told apart is running out of (GPU) memory.
+    pub fn from_panic(payload: Box<dyn std::any::Any + Send>) -> Self {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        if message.to_lowercase().contains("out of memory") {
+            Self::ResourceExhausted(message)
+        } else {
+            Self::GenProof(format!("panicked: {message}"))
+        }
+    }
+}
+
+/// How our guest programs report a witness they cannot deserialize, see the `deserialize_witness`
+/// of the circuits.
+const GUEST_WITNESS_DESERIALIZATION: [&str; 2] = [
+    "deserialisation of witness bytes failed",
+    "deserialization of witness bytes failed",
+];
+
+/// Number of lines of the guest output kept in the error of a guest failure.
+const GUEST_OUTPUT_TAIL_LINES: usize = 8;
+
+/// The last non-empty lines of the guest output, where the panic message is.
+fn guest_output_tail(guest_output: &str) -> String {
+    let lines: Vec<&str> = guest_output
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty())
+        .collect();
+    lines[lines.len().saturating_sub(GUEST_OUTPUT_TAIL_LINES)..].join("\n")
+}
+
+/// Reasons for rejecting a task before proving it, see [`Prover::admit`][crate::Prover::admit].
+#[derive(thiserror::Error, Clone, Debug, PartialEq, Eq)]
+pub enum AdmissionError {
+    /// The task is meant for another circuit or release.
+    #[error("task vk {task} does not match the loaded app vk {loaded}")]
+    VkMismatch {
+        /// Hex-encoded vk of the task.
+        task: String,
+        /// Hex-encoded app vk of the loaded release.
+        loaded: String,
+    },
+    /// The fork name of the task is not known.
+    #[error("unknown fork {0}")]
+    UnknownFork(String),
+    /// The fork of the task is not served by the loaded release.
+    #[error("fork {fork} is not served by the loaded release, supported: {supported:?}")]
+    UnsupportedFork {
+        /// Fork of the task.
+        fork: String,
+        /// Forks of the loaded release.
+        supported: Vec<String>,
+    },
+    /// The task carries no witness to read the version from.
+    #[error("task carries no witness")]
+    MissingWitness,
+    /// The version byte of the witness is not known.
+    #[error("unknown witness version {0:#04x}")]
+    UnknownVersion(u8),
+    /// The version byte of the witness belongs to another fork than the task.
+    #[error(
+        "witness version {version:#04x} is for fork {witness_fork}, task is for fork {task_fork}"
+    )]
+    VersionForkMismatch {
+        /// Version byte of the witness.
+        version: u8,
+        /// Fork of the witness version.
+        witness_fork: String,
+        /// Fork of the task.
+        task_fork: String,
+    },
+    /// The task does not specify a vk, required to route it, see
+    /// [`ProverRegistry`][crate::registry::ProverRegistry].
+    #[error("task does not specify the vk of its circuit")]
+    MissingVk,
+    /// An aggregation task was submitted without the proofs it aggregates.
+    #[error("aggregation task carries no child proofs")]
+    MissingChildProofs,
+    /// The child proofs of an aggregation task were not all generated by the same circuit.
+    #[error("child proof {index} has a different verification baseline than child proof 0")]
+    ChildBaselineMismatch {
+        /// Index of the first child proof differing from the first one.
+        index: usize,
+    },
+    /// The input commits of an aggregation task do not match its child proofs.
+    #[error("input commits of the task do not match the child proofs")]
+    InputCommitsMismatch,
+    /// No loaded release has a circuit with the vk of the task serving its fork.
+    #[error("no loaded release proves vk {vk} for fork {fork}")]
+    NoProver {
+        /// Hex-encoded vk of the task.
+        vk: String,
+        /// Fork of the task.
+        fork: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GUEST_PANIC: &str = "pi_hash = 0x00\n\
+        panicked at crates/circuits/chunk-circuit/src/circuit.rs:48:55:\n\
+        failed to execute chunk: invalid state root\n";
+
+    #[test]
+    fn retryable_variants() {
+        let retryable = [
+            Error::GenProof("sdk".to_string()),
+            Error::SelfVerification("mismatch".to_string()),
+            Error::ResourceExhausted("oom".to_string()),
+            Error::Cancelled,
+        ];
+        for e in retryable {
+            assert!(e.is_retryable(), "{e}");
+        }
+        let fatal = [
+            Error::GuestPanic {
+                message: "panic".to_string(),
+            },
+            Error::WitnessDeserialization("witness".to_string()),
+            Error::CapacityExceeded("trace height".to_string()),
+            Error::Keygen("keygen".to_string()),
+            Error::Admission(AdmissionError::MissingWitness),
+            Error::Custom("custom".to_string()),
+        ];
+        for e in fatal {
+            assert!(!e.is_retryable(), "{e}");
+        }
+    }
+
+    #[test]
+    fn sdk_error_without_known_cause() {
+        let e = Error::from_sdk(SdkError::Other(eyre::eyre!("guest panicked: exit code 1")));
+        assert!(matches!(e, Error::GenProof(_)), "{e}");
+        assert!(e.is_retryable());
+    }
+
+    #[test]
+    fn guest_failure_carries_guest_message() {
+        let e = Error::guest_failure("program exit code 1", GUEST_PANIC);
+        let Error::GuestPanic { message } = &e else {
+            panic!("unexpected error {e}");
+        };
+        assert!(message.contains("failed to execute chunk: invalid state root"));
+        assert!(message.contains("program exit code 1"));
+        assert!(!e.is_retryable());
+
+        let e = Error::guest_failure("program exit code 1", "");
+        assert!(
+            matches!(&e, Error::GuestPanic { message } if message == "program exit code 1"),
+            "{e}"
+        );
+    }
+
+    #[test]
+    fn guest_failure_on_witness() {
+        for circuit in [
+            "ChunkCircuit: deserialisation",
+            "BundleCircuit: deserialization",
+        ] {
+            let output = format!(
+                "panicked at src/circuit.rs:1:1:\n{circuit} of witness bytes failed: eof\n"
+            );
+            let e = Error::guest_failure("program exit code 1", &output);
+            assert!(matches!(e, Error::WitnessDeserialization(_)), "{e}");
+            assert!(!e.is_retryable());
+        }
+    }
+
+    #[test]
+    fn guest_output_tail_keeps_last_lines() {
+        let output: String = (0..20).map(|i| format!("line {i}\n\n")).collect();
+        let tail = guest_output_tail(&output);
+        assert_eq!(tail.lines().count(), GUEST_OUTPUT_TAIL_LINES);
+        assert!(tail.starts_with("line 12"));
+        assert!(tail.ends_with("line 19"));
+        assert_eq!(guest_output_tail(""), "");
+    }
+
+    #[test]
+    fn panics() {
+        let e = Error::from_panic(Box::new("CUDA_ERROR_OUT_OF_MEMORY: out of memory"));
+        assert!(matches!(e, Error::ResourceExhausted(_)), "{e}");
+        assert!(e.is_retryable());
+
+        let e = Error::from_panic(Box::new("index out of bounds".to_string()));
+        assert!(matches!(&e, Error::GenProof(message) if message.contains("index out of bounds")));
+
+        let e = Error::from_panic(Box::new(42));
+        assert!(matches!(&e, Error::GenProof(message) if message.contains("unknown panic")));
+    }
+}
@@ -0,0 +1,333 @@
+//! Persistent cache of the proving keys of a [`Prover`][crate::Prover], sparing the keygen when
+//! the prover restarts.
+//!
+//! The keys of a prover are stored in an entry of the cache directory named after the digest of
+//! the prover's app and aggregation configs, so that one directory can hold the keys of several
+//! circuits and releases. The app and aggregation proving keys are written once a STARK proof was
+//! generated with them, the halo2 proving key once an EVM proof was.
+//!
+//! Keys loaded from the cache are validated against the commitments of the app and the release,
+//! see [`KeyManifest`]. An entry failing the validation is discarded, and the keys generated again.
+//!
+//! Independently of the cache, the aggregation proving keys are shared by the provers of the
+//! process, see [`shared_agg_pk`], so that e.g. chunk and batch provers running side by side hold
+//! a single copy of the leaf and internal aggregation keys: their SDKs are built with the same
+//! allocation of the key. A shared key is dropped with the last SDK holding it, e.g. once all
+//! provers are [reset][crate::Prover::reset].
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, Weak},
+};
+
+use openvm_sdk::{
+    SC, Sdk,
+    config::AppConfig,
+    fs::{read_object_from_file, write_object_to_file},
+    keygen::{AggProvingKey, AppProvingKey, Halo2ProvingKey},
+};
+use openvm_sdk_config::SdkVmConfig;
+use openvm_stark_backend::keygen::types::MultiStarkVerifyingKey;
+use scroll_zkvm_types::{config::AggConfig, utils::keccak256};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Error,
+    utils::{read_json, write_json},
+};
+
+/// File name of the [`KeyManifest`] of a cache entry.
+const FD_MANIFEST: &str = "keys.json";
+
+/// Aggregation proving keys shared by the provers of the process, see [`share_agg_pk`]. The
+/// keys are held by the SDKs of the provers, not by the process.
+static SHARED_AGG_PKS: Mutex<Vec<SharedAggPk>> = Mutex::new(Vec::new());
+
+struct SharedAggPk {
+    agg_config: AggConfig,
+    agg_vk_digest: [u8; 32],
+    agg_pk: Weak<AggProvingKey>,
+}
+
+impl SharedAggPk {
+    /// The key, if held by an SDK and generated for `agg_config` with the agg vk of
+    /// `agg_vk_digest` if specified.
+    fn get(
+        &self,
+        agg_config: &AggConfig,
+        agg_vk_digest: Option<[u8; 32]>,
+    ) -> Option<Arc<AggProvingKey>> {
+        if &self.agg_config != agg_config
+            || agg_vk_digest.is_some_and(|digest| digest != self.agg_vk_digest)
+        {
+            return None;
+        }
+        self.agg_pk.upgrade()
+    }
+}
+
+/// Serializes the writes of the provers of the process, e.g. the workers of a
+/// [`ProverPool`][crate::pool::ProverPool] finishing their first proofs at once.
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+/// A proving key held in a [`KeyCache`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyKind {
+    /// The app proving key.
+    App,
+    /// The aggregation proving key.
+    Agg,
+    /// The halo2 proving key wrapping the root proof.
+    Halo2,
+}
+
+impl KeyKind {
+    /// File name of the key in a cache entry.
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Self::App => "app.pk",
+            Self::Agg => "agg.pk",
+            Self::Halo2 => "halo2.pk",
+        }
+    }
+}
+
+/// Commitments of the keys of a cache entry, written along with the keys.
+///
+/// Loaded keys must reproduce these commitments, and the commitments must match the ones of the
+/// app and of the release where it records them, i.e. the agg vk in `agg_vk.bin`. The halo2 key
+/// is derived from the agg vk, so it is validated through the agg vk.
+///
+/// Like the entry, the commitments do not depend on the app exe: releases of a circuit sharing
+/// the app config share the keys.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyManifest {
+    /// Hex-encoded vm commit of the app, see [`encode_vm_commit`].
+    pub app_vm_commit: String,
+    /// Hex-encoded digest of the agg vk, see [`agg_vk_digest`].
+    pub agg_vk_digest: String,
+}
+
+/// Keys read from a [`KeyCache`].
+#[derive(Default)]
+pub struct CachedKeys {
+    /// The manifest of the entry. No key is read without it.
+    pub manifest: Option<KeyManifest>,
+    /// The app proving key.
+    pub app_pk: Option<AppProvingKey<SdkVmConfig>>,
+    /// The aggregation proving key, shared with the SDK built from it.
+    pub agg_pk: Option<Arc<AggProvingKey>>,
+    /// The halo2 proving key.
+    pub halo2_pk: Option<Halo2ProvingKey>,
+}
+
+impl CachedKeys {
+    /// Whether no key was read.
+    pub fn is_empty(&self) -> bool {
+        self.app_pk.is_none() && self.agg_pk.is_none() && self.halo2_pk.is_none()
+    }
+}
+
+/// Entry of the proving-key cache for one app and aggregation config, see the
+/// [module docs][self].
+#[derive(Debug, Clone)]
+pub struct KeyCache {
+    dir: PathBuf,
+}
+
+impl KeyCache {
+    /// Open the entry of `dir_keys` for `app_config` and `agg_config`, creating its directory if
+    /// it does not exist.
+    pub fn open<P: AsRef<Path>>(
+        dir_keys: P,
+        app_config: &AppConfig<SdkVmConfig>,
+        agg_config: &AggConfig,
+    ) -> Result<Self, Error> {
+        // The keygen may change with the prover version, so is part of the entry too.
+        let digest = keccak256(serde_json::to_vec(&(
+            env!("CARGO_PKG_VERSION"),
+            app_config,
+            agg_config,
+        ))?);
+        let dir = dir_keys.as_ref().join(hex::encode(digest));
+        std::fs::create_dir_all(&dir).map_err(|source| Error::IoReadWrite {
+            path: dir.clone(),
+            source,
+        })?;
+        Ok(Self { dir })
+    }
+
+    /// The directory of the entry.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Path of the key of `kind`.
+    pub fn path(&self, kind: KeyKind) -> PathBuf {
+        self.dir.join(kind.file_name())
+    }
+
+    /// Read the keys of the entry. Keys that are missing or fail to deserialize are skipped, as
+    /// is the aggregation key unless `with_agg`, e.g. if it is shared, see [`shared_agg_pk`].
+    pub fn load(&self, with_agg: bool) -> CachedKeys {
+        let path_manifest = self.dir.join(FD_MANIFEST);
+        if !path_manifest.exists() {
+            return CachedKeys::default();
+        }
+        let manifest = match read_json(&path_manifest) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                tracing::warn!("ignoring cached proving keys: {e}");
+                return CachedKeys::default();
+            }
+        };
+        CachedKeys {
+            manifest: Some(manifest),
+            app_pk: self.read(KeyKind::App),
+            agg_pk: with_agg
+                .then(|| self.read(KeyKind::Agg))
+                .flatten()
+                .map(Arc::new),
+            halo2_pk: self.read(KeyKind::Halo2),
+        }
+    }
+
+    /// Write the `kinds` of keys of `sdk` missing from the entry, and the manifest if missing.
+    ///
+    /// The keys are generated by `sdk` if it did not use them yet.
+    pub fn store(&self, sdk: &Sdk, kinds: &[KeyKind], manifest: &KeyManifest) -> Result<(), Error> {
+        let _guard = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        for &kind in kinds {
+            let path = self.path(kind);
+            if path.exists() {
+                continue;
+            }
+            let t = std::time::Instant::now();
+            // Write to a temporary file first, so that a crash halfway does not leave behind a
+            // truncated key that would be picked up on the next start.
+            let path_tmp = path.with_extension("pk.tmp");
+            let written = match kind {
+                KeyKind::App => write_object_to_file(&path_tmp, sdk.app_pk()),
+                KeyKind::Agg => write_object_to_file(&path_tmp, sdk.agg_pk()),
+                KeyKind::Halo2 => write_object_to_file(&path_tmp, sdk.halo2_pk()),
+            };
+            written.map_err(|e| Error::Setup {
+                path: path_tmp.clone(),
+                src: e.to_string(),
+            })?;
+            std::fs::rename(&path_tmp, &path).map_err(|source| Error::IoReadWrite {
+                path: path.clone(),
+                source,
+            })?;
+            tracing::info!(
+                "cached {kind:?} proving key in {} ({:.2}s)",
+                path.display(),
+                t.elapsed().as_secs_f32()
+            );
+        }
+
+        let path_manifest = self.dir.join(FD_MANIFEST);
+        if !path_manifest.exists() {
+            let path_tmp = path_manifest.with_extension("json.tmp");
+            write_json(&path_tmp, manifest)?;
+            std::fs::rename(&path_tmp, &path_manifest).map_err(|source| Error::IoReadWrite {
+                path: path_manifest,
+                source,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Remove all keys of the entry.
+    pub fn clear(&self) -> Result<(), Error> {
+        for path in [
+            self.dir.join(FD_MANIFEST),
+            self.path(KeyKind::App),
+            self.path(KeyKind::Agg),
+            self.path(KeyKind::Halo2),
+        ] {
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(Error::IoReadWrite { path, source: e });
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn read<T: serde::de::DeserializeOwned>(&self, kind: KeyKind) -> Option<T> {
+        let path = self.path(kind);
+        if !path.exists() {
+            return None;
+        }
+        let t = std::time::Instant::now();
+        match read_object_from_file(&path) {
+            Ok(pk) => {
+                tracing::info!(
+                    "loaded cached {kind:?} proving key from {} ({:.2}s)",
+                    path.display(),
+                    t.elapsed().as_secs_f32()
+                );
+                Some(pk)
+            }
+            Err(e) => {
+                tracing::warn!("ignoring cached proving key {}: {e}", path.display());
+                None
+            }
+        }
+    }
+}
+
+/// Hex encoding of the vm commit of an app, see [`app_vm_commit`][crate::setup::app_vm_commit],
+/// recorded in the [`KeyManifest`]. The bytes are those of the vm commit in the app vk, see
+/// [`Prover::get_app_vk`][crate::Prover::get_app_vk].
+pub fn encode_vm_commit(vm_commit: &[u32; 8]) -> String {
+    hex::encode(
+        vm_commit
+            .iter()
+            .flat_map(|u| u.to_le_bytes())
+            .collect::<Vec<u8>>(),
+    )
+}
+
+/// Digest identifying an agg vk, recorded in the [`KeyManifest`].
+pub fn agg_vk_digest(vk: &MultiStarkVerifyingKey<SC>) -> Result<[u8; 32], Error> {
+    let bytes = bincode_v1::serialize(vk).map_err(|e| Error::Custom(e.to_string()))?;
+    Ok(keccak256(bytes).0)
+}
+
+/// The aggregation proving key shared by another prover of the process using `agg_config`, see
+/// [`share_agg_pk`]. If specified, the key must also have the agg vk of `agg_vk_digest`.
+pub fn shared_agg_pk(
+    agg_config: &AggConfig,
+    agg_vk_digest: Option<[u8; 32]>,
+) -> Option<Arc<AggProvingKey>> {
+    SHARED_AGG_PKS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .find_map(|shared| shared.get(agg_config, agg_vk_digest))
+}
+
+/// Share `agg_pk`, the key held by the SDK of a prover, generated for `agg_config` and having the
+/// agg vk of `agg_vk_digest`, with the other provers of the process. Nothing is shared if
+/// another key for them is shared already.
+///
+/// The key is not copied: the other provers build their SDKs with the same allocation, which is
+/// dropped once no SDK holds it anymore.
+pub fn share_agg_pk(agg_config: &AggConfig, agg_vk_digest: [u8; 32], agg_pk: &Arc<AggProvingKey>) {
+    let mut shared = SHARED_AGG_PKS.lock().unwrap_or_else(|e| e.into_inner());
+    shared.retain(|shared| shared.agg_pk.strong_count() > 0);
+    if shared
+        .iter()
+        .any(|shared| shared.get(agg_config, Some(agg_vk_digest)).is_some())
+    {
+        return;
+    }
+    shared.push(SharedAggPk {
+        agg_config: *agg_config,
+        agg_vk_digest,
+        agg_pk: Arc::downgrade(agg_pk),
+    });
+}
@@ -1,13 +1,33 @@
-#![feature(once_cell_try)]
-
 mod error;
-pub use error::Error;
+pub use error::{AdmissionError, Error};
+
+pub mod circuits;
+
+pub mod crash;
+
+pub mod dry_run;
+
+pub mod keys;
+
+#[cfg(feature = "coordinator")]
+pub mod coordinator;
 
 mod prover;
-pub use prover::{Prover, ProverConfig};
+pub use prover::{DeferralData, Prover, ProverConfig};
+
+pub mod pool;
+
+pub mod progress;
+
+pub mod registry;
+
+#[cfg(feature = "server")]
+pub mod server;
 
 pub mod setup;
 
+pub mod store;
+
 pub mod task;
 
 pub mod utils;
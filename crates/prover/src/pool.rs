@@ -0,0 +1,193 @@
+//! Proving several tasks in parallel with one shared [`Prover`].
+//!
+//! The workers of a [`ProverPool`] share the SDK and proving keys of the prover, so the memory
+//! of a proof in flight is mostly the tracegen and proving memory of its current segment. A task
+//! is only started once its estimated memory, [`PoolConfig::task_memory_bytes`], fits into what
+//! is left of the [`PoolConfig::memory_budget_bytes`].
+//!
+//! The peak memory reported in the stat of each proof is the one of the whole process, i.e. of
+//! all the proofs in flight.
+
+use std::sync::{
+    Arc, Condvar, Mutex,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use scroll_zkvm_types::proof::ProofEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Error, Prover,
+    progress::{CancellationToken, ProvingControl},
+    task::ProvingTask,
+};
+
+/// Configure the [`ProverPool`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PoolConfig {
+    /// Maximum number of tasks proven in parallel.
+    pub num_workers: usize,
+    /// Memory available to the proofs in flight, on top of the shared SDK.
+    pub memory_budget_bytes: u64,
+    /// Estimated peak memory of proving one task, reserved from the budget while it is proven.
+    pub task_memory_bytes: u64,
+}
+
+/// Bounded pool of workers proving tasks with a shared [`Prover`], see the
+/// [module docs][self].
+pub struct ProverPool {
+    prover: Arc<Prover>,
+    config: PoolConfig,
+    budget: MemoryBudget,
+}
+
+impl ProverPool {
+    /// Pool proving with `prover`.
+    pub fn new(prover: Arc<Prover>, config: PoolConfig) -> Result<Self, Error> {
+        if config.num_workers == 0 {
+            return Err(Error::Custom(
+                "prover pool needs at least one worker".to_string(),
+            ));
+        }
+        if config.task_memory_bytes > config.memory_budget_bytes {
+            return Err(Error::Custom(format!(
+                "a task needs {} bytes, more than the memory budget of {} bytes",
+                config.task_memory_bytes, config.memory_budget_bytes
+            )));
+        }
+        Ok(Self {
+            budget: MemoryBudget::new(config.memory_budget_bytes),
+            prover,
+            config,
+        })
+    }
+
+    /// The shared prover.
+    pub fn prover(&self) -> &Arc<Prover> {
+        &self.prover
+    }
+
+    /// Number of tasks proven at once, bounded by the workers and the memory budget.
+    pub fn parallelism(&self) -> usize {
+        let by_memory = self
+            .config
+            .memory_budget_bytes
+            .checked_div(self.config.task_memory_bytes)
+            .map_or(usize::MAX, |n| usize::try_from(n).unwrap_or(usize::MAX));
+        self.config.num_workers.min(by_memory).max(1)
+    }
+
+    /// Prove all `tasks`, see [`Prover::gen_proof_universal`]. The results are in the order of
+    /// `tasks`.
+    pub fn prove_all<T: ProvingTask + Sync>(
+        &self,
+        tasks: &[T],
+        with_snark: bool,
+    ) -> Vec<Result<ProofEnum, Error>> {
+        self.prove_all_with_cancel(tasks, with_snark, &CancellationToken::new())
+    }
+
+    /// Same as [`ProverPool::prove_all`], cancelling the proofs in flight and the tasks not yet
+    /// started once `cancel` is cancelled.
+    pub fn prove_all_with_cancel<T: ProvingTask + Sync>(
+        &self,
+        tasks: &[T],
+        with_snark: bool,
+        cancel: &CancellationToken,
+    ) -> Vec<Result<ProofEnum, Error>> {
+        let next = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<Result<ProofEnum, Error>>>> =
+            Mutex::new((0..tasks.len()).map(|_| None).collect());
+
+        std::thread::scope(|s| {
+            for worker in 0..self.parallelism().min(tasks.len()) {
+                let (next, results) = (&next, &results);
+                std::thread::Builder::new()
+                    .name(format!("prover-pool-{worker}"))
+                    .spawn_scoped(s, move || {
+                        loop {
+                            let i = next.fetch_add(1, Ordering::SeqCst);
+                            let Some(task) = tasks.get(i) else {
+                                break;
+                            };
+                            let result = self.prove_one(task, with_snark, cancel);
+                            results.lock().unwrap_or_else(|e| e.into_inner())[i] = Some(result);
+                        }
+                    })
+                    .expect("failed to spawn prover pool worker");
+            }
+        });
+
+        results
+            .into_inner()
+            .unwrap_or_else(|e| e.into_inner())
+            .into_iter()
+            .map(|result| result.unwrap_or(Err(Error::Cancelled)))
+            .collect()
+    }
+
+    fn prove_one(
+        &self,
+        task: &impl ProvingTask,
+        with_snark: bool,
+        cancel: &CancellationToken,
+    ) -> Result<ProofEnum, Error> {
+        let _reserved = self.budget.reserve(self.config.task_memory_bytes, cancel)?;
+        let control = ProvingControl::new(cancel.clone());
+        self.prover
+            .gen_proof_universal_with_control(task, with_snark, &control)
+    }
+}
+
+/// Memory shared by the proofs in flight.
+struct MemoryBudget {
+    available: Mutex<u64>,
+    cond: Condvar,
+}
+
+impl MemoryBudget {
+    fn new(bytes: u64) -> Self {
+        Self {
+            available: Mutex::new(bytes),
+            cond: Condvar::new(),
+        }
+    }
+
+    /// Block until `bytes` are available and reserve them until the returned guard is dropped.
+    fn reserve(&self, bytes: u64, cancel: &CancellationToken) -> Result<Reserved<'_>, Error> {
+        let mut available = self.available.lock().unwrap_or_else(|e| e.into_inner());
+        while *available < bytes {
+            if cancel.is_cancelled() {
+                return Err(Error::Cancelled);
+            }
+            // Woken up on release, the timeout only serves to notice cancellations.
+            available = self
+                .cond
+                .wait_timeout(available, std::time::Duration::from_millis(200))
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+        *available -= bytes;
+        Ok(Reserved {
+            budget: self,
+            bytes,
+        })
+    }
+}
+
+/// Memory reserved from a [`MemoryBudget`], released on drop.
+struct Reserved<'a> {
+    budget: &'a MemoryBudget,
+    bytes: u64,
+}
+
+impl Drop for Reserved<'_> {
+    fn drop(&mut self) {
+        *self
+            .budget
+            .available
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) += self.bytes;
+        self.budget.cond.notify_all();
+    }
+}
@@ -0,0 +1,316 @@
+//! Cancellation and progress reporting for long running proofs.
+//!
+//! A [`ProvingControl`] is handed to the `*_with_control` methods of [`Prover`][crate::Prover].
+//! The prover reports the phases it drives, see [`ProvingPhase`], and checks for cancellation at
+//! the boundaries between them.
+//!
+//! Cancellation is cooperative. A phase in flight, e.g. the SDK proving a single app segment,
+//! is not interrupted: it runs to completion and its result is abandoned. The
+//! `*_with_control` methods then return [`Error::Cancelled`] and release the SDK.
+
+use std::{
+    fmt,
+    panic::{AssertUnwindSafe, catch_unwind},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Instant,
+};
+
+use scroll_zkvm_types::proof::PhaseTimes;
+
+use crate::Error;
+
+/// Shared flag to request the cancellation of a proof.
+///
+/// Clones refer to the same flag, so the token can be handed to the thread driving the proof
+/// and cancelled from another one.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Create a token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether cancellation was requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// The phases of generating a proof, as driven by the prover.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProvingPhase {
+    /// Metered execution of the guest program, done before proving.
+    MeteredExecution,
+    /// Deferral proofs of the aggregated child proofs.
+    Deferral,
+    /// App proof of the segment `index` of the `total` segments of the execution.
+    AppSegment { index: usize, total: usize },
+    /// Leaf aggregation of the app proofs.
+    Leaf,
+    /// Internal aggregation, `layer` counting the internal layers from the leaves.
+    Internal { layer: usize },
+    /// Root proof of the aggregation tree.
+    Root,
+    /// Halo2 wrapping of the root proof into an EVM verifiable proof.
+    Halo2,
+}
+
+impl fmt::Display for ProvingPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MeteredExecution => write!(f, "metered execution"),
+            Self::Deferral => write!(f, "deferral proofs"),
+            Self::AppSegment { index, total } => write!(f, "app segment {}/{total}", index + 1),
+            Self::Leaf => write!(f, "leaf aggregation"),
+            Self::Internal { layer } => write!(f, "internal aggregation layer {}", layer + 1),
+            Self::Root => write!(f, "root proof"),
+            Self::Halo2 => write!(f, "halo2 wrap"),
+        }
+    }
+}
+
+/// Callback receiving the [`ProvingPhase`] a proof enters.
+pub type ProgressCallback = Arc<dyn Fn(&ProvingPhase) + Send + Sync>;
+
+/// Cancellation token and progress callback for a single proof.
+#[derive(Clone, Default)]
+pub struct ProvingControl {
+    cancel: CancellationToken,
+    on_progress: Option<ProgressCallback>,
+    /// The entered phases, with the instant they were entered at.
+    timeline: Arc<Mutex<Vec<(ProvingPhase, Instant)>>>,
+}
+
+impl fmt::Debug for ProvingControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProvingControl")
+            .field("cancel", &self.cancel)
+            .field("on_progress", &self.on_progress.is_some())
+            .finish()
+    }
+}
+
+impl ProvingControl {
+    /// Control that is cancelled through `cancel`.
+    pub fn new(cancel: CancellationToken) -> Self {
+        Self {
+            cancel,
+            ..Default::default()
+        }
+    }
+
+    /// Report progress to `on_progress`.
+    pub fn with_progress(
+        mut self,
+        on_progress: impl Fn(&ProvingPhase) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_progress = Some(Arc::new(on_progress));
+        self
+    }
+
+    /// The cancellation token of this control.
+    pub fn token(&self) -> &CancellationToken {
+        &self.cancel
+    }
+
+    /// Record entering `phase`, and report it.
+    fn report(&self, phase: ProvingPhase) {
+        tracing::debug!("proving phase: {phase}");
+        if let Ok(mut timeline) = self.timeline.lock() {
+            timeline.push((phase.clone(), Instant::now()));
+        }
+        if let Some(on_progress) = &self.on_progress {
+            on_progress(&phase);
+        }
+    }
+
+    /// Wall time spent in each phase entered so far, the last phase ending at `end`.
+    ///
+    /// The metered execution is not included, it is accounted for separately.
+    pub(crate) fn phase_times(&self, end: Instant) -> PhaseTimes {
+        let mut times = PhaseTimes::default();
+        let Ok(timeline) = self.timeline.lock() else {
+            return times;
+        };
+        let ends = timeline.iter().skip(1).map(|(_, at)| *at).chain([end]);
+        for ((phase, start), end) in timeline.iter().zip(ends) {
+            let mills = end.saturating_duration_since(*start).as_millis() as u64;
+            let slot = match phase {
+                ProvingPhase::MeteredExecution => continue,
+                ProvingPhase::Deferral => &mut times.deferral,
+                ProvingPhase::AppSegment { .. } => &mut times.app,
+                ProvingPhase::Leaf => &mut times.leaf,
+                ProvingPhase::Internal { .. } => &mut times.internal,
+                ProvingPhase::Root => &mut times.root,
+                ProvingPhase::Halo2 => &mut times.halo2,
+            };
+            *slot += mills;
+        }
+        times
+    }
+
+    /// Return [`Error::Cancelled`] if cancellation was requested.
+    pub fn check_cancelled(&self) -> Result<(), Error> {
+        if self.cancel.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        Ok(())
+    }
+
+    /// Run `f`, the work of `phase`, under this control.
+    ///
+    /// Cancellation is checked before entering `phase` and once `f` returns: `f` is not
+    /// interrupted, and its result is abandoned if cancellation was requested meanwhile.
+    ///
+    /// A panic raised by `f` is turned into an error, see [`Error::from_panic`].
+    pub(crate) fn run<T>(&self, phase: ProvingPhase, f: impl FnOnce() -> T) -> Result<T, Error> {
+        self.check_cancelled()?;
+        self.report(phase);
+        let value = catch_unwind(AssertUnwindSafe(f)).map_err(Error::from_panic)?;
+        self.check_cancelled()?;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn token_clones_share_cancellation() {
+        let token = CancellationToken::new();
+        let control = ProvingControl::new(token.clone());
+        assert!(!control.token().is_cancelled());
+        assert!(control.check_cancelled().is_ok());
+
+        token.cancel();
+        assert!(control.token().is_cancelled());
+        assert!(matches!(control.check_cancelled(), Err(Error::Cancelled)));
+    }
+
+    #[test]
+    fn run_reports_phase_and_returns_value() {
+        let phases = Arc::new(Mutex::new(Vec::new()));
+        let control = ProvingControl::default().with_progress({
+            let phases = phases.clone();
+            move |phase| phases.lock().unwrap().push(phase.clone())
+        });
+
+        assert_eq!(
+            control.run(ProvingPhase::MeteredExecution, || 1).unwrap(),
+            1
+        );
+        assert_eq!(control.run(ProvingPhase::Leaf, || 2).unwrap(), 2);
+        assert_eq!(
+            *phases.lock().unwrap(),
+            [ProvingPhase::MeteredExecution, ProvingPhase::Leaf]
+        );
+    }
+
+    #[test]
+    fn phases_display_progress() {
+        let phases = [
+            ProvingPhase::AppSegment { index: 2, total: 5 },
+            ProvingPhase::Leaf,
+            ProvingPhase::Internal { layer: 0 },
+            ProvingPhase::Root,
+        ];
+        assert_eq!(
+            phases.map(|phase| phase.to_string()),
+            [
+                "app segment 3/5",
+                "leaf aggregation",
+                "internal aggregation layer 1",
+                "root proof",
+            ]
+        );
+    }
+
+    #[test]
+    fn run_skips_cancelled_phase() {
+        let control = ProvingControl::default();
+        control.token().cancel();
+
+        let mut ran = false;
+        let result = control.run(ProvingPhase::Root, || ran = true);
+        assert!(matches!(result, Err(Error::Cancelled)));
+        assert!(!ran);
+        assert!(control.timeline.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn run_abandons_result_cancelled_meanwhile() {
+        let control = ProvingControl::default();
+        let token = control.token().clone();
+
+        let result = control.run(ProvingPhase::Leaf, || token.cancel());
+        assert!(matches!(result, Err(Error::Cancelled)));
+    }
+
+    #[test]
+    fn run_turns_panic_into_error() {
+        let control = ProvingControl::default();
+
+        let result = control.run(ProvingPhase::Root, || panic!("boom"));
+        let err = result.unwrap_err();
+        assert!(!matches!(err, Error::Cancelled));
+        assert!(err.to_string().contains("boom"), "{err}");
+    }
+
+    #[test]
+    fn phase_times_map_phases() {
+        let control = ProvingControl::default();
+        let start = Instant::now();
+        let at = |mills| start + Duration::from_millis(mills);
+        *control.timeline.lock().unwrap() = vec![
+            (ProvingPhase::Deferral, at(0)),
+            (ProvingPhase::MeteredExecution, at(10)),
+            (ProvingPhase::AppSegment { index: 0, total: 1 }, at(30)),
+            (ProvingPhase::Leaf, at(60)),
+            (ProvingPhase::Internal { layer: 0 }, at(70)),
+            (ProvingPhase::Root, at(90)),
+            (ProvingPhase::Halo2, at(100)),
+        ];
+
+        assert_eq!(
+            control.phase_times(at(150)),
+            PhaseTimes {
+                deferral: 10,
+                app: 30,
+                leaf: 10,
+                internal: 20,
+                root: 10,
+                halo2: 50,
+            }
+        );
+    }
+
+    #[test]
+    fn phase_times_add_up_repeated_phases() {
+        let control = ProvingControl::default();
+        let start = Instant::now();
+        let at = |mills| start + Duration::from_millis(mills);
+        *control.timeline.lock().unwrap() = vec![
+            (ProvingPhase::AppSegment { index: 0, total: 2 }, at(0)),
+            (ProvingPhase::AppSegment { index: 1, total: 2 }, at(20)),
+            (ProvingPhase::MeteredExecution, at(30)),
+            (ProvingPhase::Leaf, at(35)),
+        ];
+
+        assert_eq!(control.phase_times(at(40)).app, 30);
+        assert_eq!(control.phase_times(at(40)).leaf, 5);
+        assert_eq!(control.phase_times(at(40)).halo2, 0);
+    }
+}
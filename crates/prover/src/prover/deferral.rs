@@ -0,0 +1,199 @@
+//! Proving aggregation tasks (batch, bundle), whose child STARK proofs are verified through
+//! deferral, see [`Prover::enable_deferral`].
+
+use openvm_circuit::arch::deferral::DeferralState;
+use openvm_continuations::CommitBytes;
+use openvm_sdk::DeferralInput;
+use openvm_stark_sdk::config::baby_bear_poseidon2::Digest;
+use openvm_verify_stark_circuit::extension::{get_deferral_state, get_raw_deferral_results};
+use openvm_verify_stark_host::{
+    VmStarkProof,
+    vk::{VerificationBaseline, VmStarkVerifyingKey},
+};
+use scroll_zkvm_types::{
+    proof::{ProofEnum, StarkProof},
+    task::ProvingTask as UniversalProvingTask,
+};
+
+use super::Prover;
+use crate::{
+    AdmissionError, Error,
+    dry_run::DryRunReport,
+    progress::{ProvingControl, ProvingPhase},
+    utils::vm::decode_stark_proof,
+};
+
+/// Deferral data of the child proofs of an aggregation task, see
+/// [`Prover::compute_deferral_data`].
+pub struct DeferralData {
+    /// Commits of the child proofs, read by the guest, see
+    /// [`ProvingTask::input_commits`][UniversalProvingTask::input_commits].
+    pub input_commits: Vec<[u8; 32]>,
+    /// Inputs of the deferral prover.
+    pub inputs: Vec<DeferralInput>,
+    /// Deferral states written to the guest's stdin.
+    pub states: Vec<DeferralState>,
+}
+
+impl Prover {
+    /// The cached commit of the verify-stark deferral circuit.
+    pub fn deferral_cached_commit(&self) -> Result<CommitBytes, Error> {
+        let mut commits = self
+            .get_sdk()?
+            .deferral_circuit_cached_commits(0)
+            .map_err(|e| Error::Keygen(format!("failed to get deferral cached commits: {e}")))?;
+        if commits.len() != 1 {
+            return Err(Error::Keygen(format!(
+                "expected one deferral circuit, got {}",
+                commits.len()
+            )));
+        }
+        Ok(commits.pop().expect("exactly one commit"))
+    }
+
+    /// Compute the deferral data of `child_proofs`, the proofs aggregated by a task of this
+    /// prover. The child proofs must all be generated by the same circuit, i.e. share the same
+    /// verification baseline: app exe commit, app vk commit and deferral hook commit.
+    ///
+    /// Requires [`Prover::enable_deferral`].
+    pub fn compute_deferral_data(
+        &self,
+        child_proofs: &[StarkProof],
+    ) -> Result<DeferralData, Error> {
+        let deferral = self.deferral.as_ref().ok_or_else(|| {
+            Error::Custom(format!(
+                "deferral is not enabled for {}, see Prover::enable_deferral",
+                self.prover_name
+            ))
+        })?;
+
+        let (vm_proofs, baselines): (Vec<VmStarkProof>, Vec<VerificationBaseline>) = child_proofs
+            .iter()
+            .map(decode_stark_proof)
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .unzip();
+
+        let baseline = baselines
+            .first()
+            .cloned()
+            .ok_or(AdmissionError::MissingChildProofs)?;
+        if let Some(index) = baselines.iter().position(|b| {
+            b.app_exe_commit != baseline.app_exe_commit
+                || b.app_vk_commit != baseline.app_vk_commit
+                || b.def_hook_commit != baseline.def_hook_commit
+        }) {
+            return Err(AdmissionError::ChildBaselineMismatch { index }.into());
+        }
+
+        let vk = VmStarkVerifyingKey {
+            mvk: (*deferral.child_agg_vk).clone(),
+            baseline,
+        };
+        let cached_commit: Digest = self.deferral_cached_commit()?.into();
+
+        let raw_results = get_raw_deferral_results(&vk, &vm_proofs, cached_commit)
+            .map_err(|e| Error::Custom(format!("get_raw_deferral_results failed: {e}")))?;
+        let input_commits = raw_results
+            .iter()
+            .map(|r| {
+                r.input.as_slice().try_into().map_err(|_| {
+                    Error::Custom(format!(
+                        "input commit must be 32 bytes, got {}",
+                        r.input.len()
+                    ))
+                })
+            })
+            .collect::<Result<Vec<[u8; 32]>, _>>()?;
+
+        let inputs = vec![DeferralInput::from_inputs(&vm_proofs)];
+        let state = get_deferral_state(&vk, &vm_proofs, cached_commit, 0)
+            .map_err(|e| Error::Custom(format!("get_deferral_state failed: {e}")))?;
+
+        Ok(DeferralData {
+            input_commits,
+            inputs,
+            states: vec![state],
+        })
+    }
+
+    /// Prove an aggregation task, i.e. a batch or bundle task, aggregating `child_proofs`.
+    ///
+    /// The task is checked by [`Prover::admit`] before the deferral data of the child proofs is
+    /// computed, see [`Prover::compute_deferral_data`], and their input commits are set on the
+    /// task. A task already carrying input commits must carry those of `child_proofs`.
+    /// Otherwise the same as [`Prover::gen_proof_universal`].
+    ///
+    /// Requires [`Prover::enable_deferral`].
+    #[tracing::instrument("Prover::prove_aggregation", skip_all, fields(task_id = task.identifier))]
+    pub fn prove_aggregation(
+        &self,
+        task: &UniversalProvingTask,
+        child_proofs: &[StarkProof],
+        with_snark: bool,
+    ) -> Result<ProofEnum, Error> {
+        self.prove_aggregation_with_control(
+            task,
+            child_proofs,
+            with_snark,
+            &ProvingControl::default(),
+        )
+    }
+
+    /// Same as [`Prover::prove_aggregation`], reporting progress to and honouring cancellation
+    /// through `control`.
+    pub fn prove_aggregation_with_control(
+        &self,
+        task: &UniversalProvingTask,
+        child_proofs: &[StarkProof],
+        with_snark: bool,
+        control: &ProvingControl,
+    ) -> Result<ProofEnum, Error> {
+        // A misrouted task is rejected before the costly deferral data of its children.
+        self.admit(task)?;
+        let deferral = control.run(ProvingPhase::Deferral, || {
+            self.compute_deferral_data(child_proofs)
+        });
+        let deferral = self.reset_if_cancelled(deferral)??;
+        let task = with_input_commits(task, &deferral)?;
+        self.prove_task(&task, Some(&deferral), with_snark, control)
+    }
+
+    /// Execute an aggregation task aggregating `child_proofs` without proving it, see
+    /// [`Prover::dry_run`].
+    ///
+    /// The deferral data of the child proofs is computed and checked against the task as in
+    /// [`Prover::prove_aggregation`], and the guest executes with their deferral states.
+    ///
+    /// Requires [`Prover::enable_deferral`].
+    #[tracing::instrument(
+        "Prover::dry_run_aggregation",
+        skip_all,
+        fields(task_id = task.identifier)
+    )]
+    pub fn dry_run_aggregation(
+        &self,
+        task: &UniversalProvingTask,
+        child_proofs: &[StarkProof],
+    ) -> Result<DryRunReport, Error> {
+        self.admit(task)?;
+        let deferral = self.compute_deferral_data(child_proofs)?;
+        let task = with_input_commits(task, &deferral)?;
+        self.dry_run_task(&task, Some(&deferral))
+    }
+}
+
+/// `task` carrying the input commits of `deferral`. A task already carrying input commits must
+/// carry those of `deferral`.
+fn with_input_commits(
+    task: &UniversalProvingTask,
+    deferral: &DeferralData,
+) -> Result<UniversalProvingTask, Error> {
+    if !task.input_commits.is_empty() && task.input_commits != deferral.input_commits {
+        return Err(AdmissionError::InputCommitsMismatch.into());
+    }
+    Ok(UniversalProvingTask {
+        input_commits: deferral.input_commits.clone(),
+        ..task.clone()
+    })
+}
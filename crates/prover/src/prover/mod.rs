@@ -1,26 +1,28 @@
 use std::{
-    path::PathBuf,
-    sync::{Arc, OnceLock},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
 };
 
 use openvm_circuit::arch::instructions::{DEFERRAL_AS, exe::VmExe};
 use openvm_continuations::CommitBytes;
 use openvm_recursion_circuit::batch_constraint::commit_child_vk;
-use openvm_sdk::config::{
-    AggregationConfig, AggregationSystemParams, AggregationTreeConfig, AppConfig,
-};
+use openvm_sdk::config::{AggregationConfig, AggregationSystemParams, AppConfig};
+use openvm_sdk::keygen::AppProvingKey;
 use openvm_sdk::prover::{DeferralAggProver, MultiDeferralCircuitProver};
 use openvm_sdk::{F, SC, Sdk, StdIn};
 use openvm_sdk_config::{SdkVmConfig, deferral::SupportedDeferral};
-use openvm_stark_backend::StarkEngine;
+use openvm_stark_backend::{StarkEngine, keygen::types::MultiStarkVerifyingKey};
 use openvm_stark_sdk::{
-    config::{
-        hook_params_with_100_bits_security, internal_params_with_100_bits_security,
-        leaf_params_with_100_bits_security,
-    },
+    config::{hook_params_with_100_bits_security, internal_params_with_100_bits_security},
     openvm_stark_backend::{codec::Encode, p3_field::PrimeField32},
 };
-use scroll_zkvm_types::{proof::OpenVmEvmProof, types_agg::ProgramCommitment, utils::serialize_vk};
+use scroll_zkvm_types::{
+    config::{AggConfig, FD_AGG_CONFIG, FD_DEF_HOOK_COMMIT},
+    proof::OpenVmEvmProof,
+    types_agg::ProgramCommitment,
+    utils::serialize_vk,
+    version::Version,
+};
 use scroll_zkvm_verifier::verifier::UniversalVerifier;
 use tracing::instrument;
 
@@ -43,24 +45,25 @@ type SdkAppConfig = AppConfig<SdkVmConfig>;
 // Re-export from openvm_sdk.
 pub use openvm_sdk::{self};
 
-/// Default aggregation parameters shared by all provers.
-fn default_agg_params() -> AggregationSystemParams {
-    AggregationSystemParams {
-        leaf: leaf_params_with_100_bits_security(),
-        internal: internal_params_with_100_bits_security(),
-    }
-}
+mod deferral;
+pub use deferral::DeferralData;
+mod stark;
+use stark::VmStarkOutput;
 
-/// Default aggregation-tree shape used by chunk, batch, and bundle provers.
-///
-/// Must stay in sync with [`generate_evm_verifier`](crates/build-guest/src/main.rs).
-const DEFAULT_AGG_TREE_CONFIG: AggregationTreeConfig = AggregationTreeConfig {
-    num_children_internal: 3,
-    num_children_leaf: 4,
+use crate::crash::CrashInfo;
+use crate::dry_run::{DryRunReport, pi_hash_from_public_values};
+use crate::keys::{
+    CachedKeys, KeyCache, KeyKind, KeyManifest, agg_vk_digest, encode_vm_commit, share_agg_pk,
+    shared_agg_pk,
 };
-
-use crate::setup::read_app_exe;
-use crate::{Error, setup::read_app_config, task::ProvingTask};
+use crate::progress::{ProvingControl, ProvingPhase};
+use crate::setup::{
+    FD_RELEASE_VKS, app_vm_commit, compute_program_commitment_with_app_pk, read_app_exe,
+    read_release_vks,
+};
+use crate::store::{FsProofStore, ProofKey, ProofKind, ProofStore};
+use crate::utils::vm::{ExecutionResult, GuestOutput, SegmentedExecutionResult};
+use crate::{AdmissionError, Error, setup::read_app_config, task::ProvingTask};
 
 use scroll_zkvm_types::proof::{EvmProof, ProofEnum, StarkProof, StarkProofStat};
 
@@ -74,8 +77,38 @@ pub struct Prover {
     pub config: ProverConfig,
     /// SDKConfig
     app_config: SdkAppConfig,
-    /// Lazily initialized SDK
-    sdk: OnceLock<Sdk>,
+    /// Commitment of the app, computed at setup, see [`Prover::get_app_commitment`].
+    app_commitment: ProgramCommitment,
+    /// Aggregation config, resolved at setup, see [`ProverConfig::agg_config`].
+    agg_config: AggConfig,
+    /// App proving key generated at setup to compute the app commitment, if not recorded by the
+    /// release. Taken by the first SDK build, so that the app keygen does not run again.
+    setup_app_pk: Mutex<Option<AppProvingKey<SdkVmConfig>>>,
+    /// Lazily initialized SDK, shared by the proofs running concurrently.
+    sdk: RwLock<Option<Arc<Sdk>>>,
+    /// Deferral setup of an aggregation circuit, see [`Prover::enable_deferral`].
+    deferral: Option<DeferralSetup>,
+    /// Optional store of already generated proofs.
+    proof_store: Option<Arc<dyn ProofStore>>,
+}
+
+// Provers are shared by the workers of a [`ProverPool`][crate::pool::ProverPool].
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Prover>();
+};
+
+/// What is needed to rebuild the deferral prover of an aggregation circuit, see
+/// [`Prover::enable_deferral`].
+struct DeferralSetup {
+    /// Aggregation VK of the child circuit.
+    child_agg_vk: Arc<MultiStarkVerifyingKey<SC>>,
+    /// Internal-recursive VK commit of the child circuit.
+    internal_recursive_cached_commit: CommitBytes,
+    /// Deferral hook commit of the child circuit, if it uses deferral itself.
+    child_def_hook_commit: Option<CommitBytes>,
+    /// The deferral prover built by [`Prover::enable_deferral`], taken by the first SDK build.
+    agg_prover: Mutex<Option<DeferralAggProver>>,
 }
 
 /// Configure the [`Prover`].
@@ -85,6 +118,25 @@ pub struct ProverConfig {
     pub path_app_exe: PathBuf,
     /// Path to find application's OpenVM config.
     pub path_app_config: PathBuf,
+    /// Optional directory to persist generated proofs in, see [`FsProofStore`].
+    pub dir_cache: Option<PathBuf>,
+    /// Optional directory to cache the proving keys in across restarts, see [`KeyCache`].
+    pub dir_keys: Option<PathBuf>,
+    /// Optional directory to write a crash bundle in when executing or proving a task fails, to
+    /// replay the failure offline, see [`crate::crash`].
+    pub dir_crash: Option<PathBuf>,
+    /// Name of the guest release of the app, e.g. `galileov2`, reported in the stats of its
+    /// proofs.
+    pub release: Option<String>,
+    /// Forks the release can prove, e.g. `galileov2`. Tasks of other forks are rejected by
+    /// [`Prover::admit`]. Empty to accept any fork.
+    pub forks: Vec<String>,
+    /// Aggregation config to prove with. `None` to use the config recorded by build-guest next
+    /// to `app.vmexe`, or the default config for releases that did not record one.
+    ///
+    /// The config is checked at setup against the ones recorded for the agg vk and the EVM
+    /// verifier of the release.
+    pub agg_config: Option<AggConfig>,
 }
 
 impl Prover {
@@ -93,18 +145,133 @@ impl Prover {
     pub fn setup(config: ProverConfig, name: Option<&str>) -> Result<Self, Error> {
         let app_config = read_app_config(&config.path_app_config)?;
         let app_exe = read_app_exe(&config.path_app_exe)?;
+        let agg_config = Self::resolve_agg_config(&config)?;
+        let (app_commitment, app_pk) = match Self::release_app_commitment(&config, name)? {
+            Some(app_commitment) => (app_commitment, None),
+            None => {
+                let (app_commitment, app_pk) =
+                    compute_program_commitment_with_app_pk(app_exe.clone(), app_config.clone())?;
+                (app_commitment, Some(app_pk))
+            }
+        };
+        let proof_store = match &config.dir_cache {
+            Some(dir) => Some(Arc::new(FsProofStore::new(dir)?) as Arc<dyn ProofStore>),
+            None => None,
+        };
         Ok(Self {
             app_exe: Arc::new(app_exe),
             config,
             prover_name: name.unwrap_or("universal").to_string(),
             app_config,
-            sdk: OnceLock::new(),
+            app_commitment,
+            agg_config,
+            setup_app_pk: Mutex::new(app_pk),
+            sdk: RwLock::new(None),
+            deferral: None,
+            proof_store,
         })
     }
 
-    /// Release OpenVM SDK resources
-    pub fn reset(&mut self) {
-        self.sdk = OnceLock::new();
+    /// The app commitment of the circuit `name` recorded by the release in
+    /// [`FD_RELEASE_VKS`], if any, so that setting up a prover does not run the app keygen.
+    fn release_app_commitment(
+        config: &ProverConfig,
+        name: Option<&str>,
+    ) -> Result<Option<ProgramCommitment>, Error> {
+        let (Some(name), Some(path_vks)) = (name, Self::release_vks_path(config)) else {
+            return Ok(None);
+        };
+        let Some(vk) = read_release_vks(&path_vks)?.remove(name) else {
+            return Ok(None);
+        };
+        if vk.len() != 64 {
+            return Err(Error::Setup {
+                path: path_vks,
+                src: format!("vk of circuit {name} has {} bytes, expected 64", vk.len()),
+            });
+        }
+        Ok(Some(serialize_vk::deserialize(&vk)))
+    }
+
+    /// Path of the app vks of the release, see [`FD_RELEASE_VKS`], if recorded.
+    fn release_vks_path(config: &ProverConfig) -> Option<PathBuf> {
+        config
+            .path_app_exe
+            .parent()
+            .and_then(Path::parent)
+            .map(|dir| dir.join(FD_RELEASE_VKS))
+            .filter(|path| path.exists())
+    }
+
+    /// Resolve the aggregation config of `config`, checking that it matches the configs that the
+    /// agg vk (`<circuit>/agg_config.json`) and the EVM verifier (`verifier/agg_config.json`) of
+    /// the release were built with, if recorded. A config that is not recorded cannot be
+    /// checked, which is logged.
+    fn resolve_agg_config(config: &ProverConfig) -> Result<AggConfig, Error> {
+        let dir_assets = config
+            .path_app_exe
+            .parent()
+            .map(PathBuf::from)
+            .unwrap_or_default();
+        let read = |dir: &std::path::Path| {
+            AggConfig::read_from_dir(dir).map_err(|e| Error::Setup {
+                path: dir.join(FD_AGG_CONFIG),
+                src: e.to_string(),
+            })
+        };
+
+        let recorded_agg_vk = read(&dir_assets)?;
+        let agg_config = config.agg_config.or(recorded_agg_vk).unwrap_or_default();
+        agg_config.agg_params().map_err(|src| Error::Setup {
+            path: dir_assets.clone(),
+            src,
+        })?;
+
+        let mut recorded = vec![(dir_assets.clone(), recorded_agg_vk)];
+        if let Some(dir_verifier) = dir_assets.parent().map(|dir| dir.join("verifier")) {
+            let recorded_verifier = read(&dir_verifier)?;
+            recorded.push((dir_verifier, recorded_verifier));
+        }
+        for (dir, recorded) in recorded {
+            match recorded {
+                Some(recorded) if recorded != agg_config => {
+                    return Err(Error::Setup {
+                        path: dir.join(FD_AGG_CONFIG),
+                        src: format!(
+                            "aggregation config {agg_config:?} does not match the release's {recorded:?}"
+                        ),
+                    });
+                }
+                Some(_) => {}
+                None => tracing::warn!(
+                    "{} not found, aggregation config {agg_config:?} not checked against it",
+                    dir.join(FD_AGG_CONFIG).display()
+                ),
+            }
+        }
+
+        Ok(agg_config)
+    }
+
+    /// The aggregation config the prover was set up with, see [`ProverConfig::agg_config`].
+    pub fn agg_config(&self) -> &AggConfig {
+        &self.agg_config
+    }
+
+    fn agg_params(&self) -> Result<AggregationSystemParams, Error> {
+        self.agg_config.agg_params().map_err(Error::Keygen)
+    }
+
+    /// Use `store` to look up and persist proofs in [`Prover::gen_proof_universal`], replacing
+    /// the store configured by [`ProverConfig::dir_cache`].
+    pub fn set_proof_store(&mut self, store: Option<Arc<dyn ProofStore>>) {
+        self.proof_store = store;
+    }
+
+    /// Release OpenVM SDK resources. Proofs in flight keep using the SDK until they finish, the
+    /// next proof initializes it again.
+    pub fn reset(&self) {
+        *self.sdk.write().unwrap_or_else(|e| e.into_inner()) = None;
     }
 
     /// Get or initialize the SDK lazily.
@@ -112,34 +279,225 @@ impl Prover {
     /// For leaf circuits (chunk) this returns a plain SDK.  
     /// For aggregation circuits (batch/bundle) call [`enable_deferral`] first so
     /// the SDK includes the deferral prover required by OpenVM v2+.
-    fn get_sdk(&self) -> Result<&Sdk, Error> {
-        self.sdk.get_or_try_init(|| {
-            tracing::info!("Lazy initializing SDK...");
-            let sdk = Sdk::builder()
-                .app_config(self.app_config.clone())
-                .agg_params(default_agg_params())
-                .agg_tree_config(DEFAULT_AGG_TREE_CONFIG)
-                .build()
-                .map_err(|e| Error::GenProof(e.to_string()))?;
-            Ok(sdk)
-        })
+    ///
+    /// Concurrent callers share the same SDK, which is initialized only once.
+    fn get_sdk(&self) -> Result<Arc<Sdk>, Error> {
+        if let Some(sdk) = self.sdk.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            return Ok(sdk.clone());
+        }
+        let mut sdk = self.sdk.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(sdk) = sdk.as_ref() {
+            return Ok(sdk.clone());
+        }
+        tracing::info!("Lazy initializing SDK...");
+        let deferral_agg_prover = match &self.deferral {
+            Some(deferral) => {
+                let built = deferral
+                    .agg_prover
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .take();
+                match built {
+                    Some(deferral_agg_prover) => Some(deferral_agg_prover),
+                    None => Some(self.deferral_agg_prover(deferral)?),
+                }
+            }
+            None => None,
+        };
+        let built = Arc::new(self.build_sdk(deferral_agg_prover)?);
+        *sdk = Some(built.clone());
+        Ok(built)
+    }
+
+    /// Build the SDK, with the aggregation proving key shared by the other provers of the
+    /// process if any, see [`shared_agg_pk`], and the proving keys of the [`KeyCache`] if
+    /// configured, see [`ProverConfig::dir_keys`].
+    fn build_sdk(&self, deferral_agg_prover: Option<DeferralAggProver>) -> Result<Sdk, Error> {
+        let release_agg_vk_digest = self.release_agg_vk_digest()?;
+        let shared = shared_agg_pk(&self.agg_config, release_agg_vk_digest);
+        if shared.is_some() {
+            tracing::info!("{} shares the aggregation proving key", self.prover_name);
+        }
+
+        let cache = self.key_cache()?;
+        let mut keys = match &cache {
+            Some(cache) => cache.load(shared.is_none()),
+            None => CachedKeys::default(),
+        };
+        let manifest = keys.manifest.clone().filter(|_| !keys.is_empty());
+        let validate_app = keys.app_pk.is_some();
+        let validate_agg = keys.agg_pk.is_some() || keys.halo2_pk.is_some();
+        let setup_app_pk = self
+            .setup_app_pk
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        if keys.app_pk.is_none() {
+            keys.app_pk = setup_app_pk;
+        }
+        if let Some(shared) = &shared {
+            keys.agg_pk = Some(shared.clone());
+        }
+
+        let mut sdk = self.build_sdk_with_keys(deferral_agg_prover, keys)?;
+        if let (Some(cache), Some(manifest)) = (&cache, &manifest) {
+            let validated = self.validate_cached_keys(
+                &sdk,
+                manifest,
+                release_agg_vk_digest,
+                validate_app,
+                validate_agg,
+            );
+            if let Err(src) = validated {
+                tracing::warn!(
+                    "discarding cached proving keys in {}: {src}",
+                    cache.dir().display()
+                );
+                drop(sdk);
+                cache.clear()?;
+                let deferral_agg_prover = match &self.deferral {
+                    Some(deferral) => Some(self.deferral_agg_prover(deferral)?),
+                    None => None,
+                };
+                let keys = CachedKeys {
+                    agg_pk: shared.clone(),
+                    ..Default::default()
+                };
+                sdk = self.build_sdk_with_keys(deferral_agg_prover, keys)?;
+            }
+        }
+
+        share_agg_pk(
+            &self.agg_config,
+            agg_vk_digest(&sdk.agg_vk())?,
+            &sdk.agg_pk_shared(),
+        );
+        Ok(sdk)
+    }
+
+    fn build_sdk_with_keys(
+        &self,
+        deferral_agg_prover: Option<DeferralAggProver>,
+        keys: CachedKeys,
+    ) -> Result<Sdk, Error> {
+        let mut builder = Sdk::builder()
+            .app_config(self.app_config.clone())
+            .agg_params(self.agg_params()?)
+            .agg_tree_config(self.agg_config.agg_tree_config());
+        if let Some(deferral_agg_prover) = deferral_agg_prover {
+            builder = builder.deferral_agg_prover(deferral_agg_prover);
+        }
+        if let Some(app_pk) = keys.app_pk {
+            builder = builder.app_pk(app_pk);
+        }
+        // The key is shared with the SDK, not copied into it.
+        if let Some(agg_pk) = keys.agg_pk {
+            builder = builder.agg_pk_shared(agg_pk);
+        }
+        if let Some(halo2_pk) = keys.halo2_pk {
+            builder = builder.halo2_pk(halo2_pk);
+        }
+        builder.build().map_err(|e| Error::Keygen(e.to_string()))
+    }
+
+    /// The entry of the [`KeyCache`] for the current app config, if configured.
+    fn key_cache(&self) -> Result<Option<KeyCache>, Error> {
+        self.config
+            .dir_keys
+            .as_ref()
+            .map(|dir| KeyCache::open(dir, &self.app_config, &self.agg_config))
+            .transpose()
+    }
+
+    /// Digest of the agg vk recorded by the release next to `app.vmexe`, if any.
+    fn release_agg_vk_digest(&self) -> Result<Option<[u8; 32]>, Error> {
+        let path = self.config.path_app_exe.with_file_name(Self::FD_AGG_VK);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let vk: MultiStarkVerifyingKey<SC> =
+            openvm_sdk::fs::read_object_from_file(&path).map_err(|e| Error::Setup {
+                path: path.clone(),
+                src: e.to_string(),
+            })?;
+        agg_vk_digest(&vk).map(Some)
+    }
+
+    /// Check that the cached keys `sdk` was built with reproduce the commitments of the
+    /// `manifest`, and that these match the ones of the app and of the release.
+    fn validate_cached_keys(
+        &self,
+        sdk: &Sdk,
+        manifest: &KeyManifest,
+        release_agg_vk_digest: Option<[u8; 32]>,
+        validate_app: bool,
+        validate_agg: bool,
+    ) -> Result<(), String> {
+        if validate_app {
+            let vm_commit = encode_vm_commit(&app_vm_commit(sdk));
+            if vm_commit != manifest.app_vm_commit {
+                return Err(format!(
+                    "app vm commit {vm_commit} differs from the recorded {}",
+                    manifest.app_vm_commit
+                ));
+            }
+            let expected = encode_vm_commit(&self.app_commitment.vm);
+            if vm_commit != expected {
+                return Err(format!(
+                    "app vm commit {vm_commit} differs from the app's {expected}"
+                ));
+            }
+        }
+        if validate_agg {
+            let digest = hex::encode(agg_vk_digest(&sdk.agg_vk()).map_err(|e| e.to_string())?);
+            if digest != manifest.agg_vk_digest {
+                return Err(format!(
+                    "agg vk digest {digest} differs from the recorded {}",
+                    manifest.agg_vk_digest
+                ));
+            }
+            if let Some(release_digest) = release_agg_vk_digest.map(hex::encode) {
+                if release_digest != digest {
+                    return Err(format!(
+                        "agg vk digest {digest} differs from the release's {release_digest}"
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the `kinds` of proving keys of `sdk` to the [`KeyCache`], if configured and not
+    /// cached yet. A failure only costs the keygen on the next start, so it is logged.
+    fn persist_keys(&self, sdk: &Sdk, kinds: &[KeyKind]) {
+        let result = self.key_cache().and_then(|cache| {
+            let Some(cache) = cache else {
+                return Ok(());
+            };
+            if kinds.iter().all(|&kind| cache.path(kind).exists()) {
+                return Ok(());
+            }
+            let manifest = KeyManifest {
+                app_vm_commit: encode_vm_commit(&self.app_commitment.vm),
+                agg_vk_digest: hex::encode(agg_vk_digest(&sdk.agg_vk())?),
+            };
+            cache.store(sdk, kinds, &manifest)
+        });
+        if let Err(e) = result {
+            tracing::warn!("failed to cache the proving keys: {e}");
+        }
     }
 
     /// Pick up loaded app commit, to distinguish from which circuit the proof comes
+    ///
+    /// Read at setup from the vks recorded by the release, or computed with only the app keys
+    /// for releases that did not record them, see [`compute_program_commitment`].
     pub fn get_app_commitment(&self) -> ProgramCommitment {
-        let sdk = self.get_sdk().expect("Failed to initialize SDK");
-        let prover = sdk
-            .prover(self.app_exe.clone())
-            .expect("Failed to initialize prover");
-        let exe_digest = prover.app_prover.app_exe_commit();
-        let vm_digest = prover.app_vm_commit();
-        let exe: [u32; 8] = std::array::from_fn(|i| exe_digest[i].as_canonical_u32());
-        let vm: [u32; 8] = std::array::from_fn(|i| vm_digest[i].as_canonical_u32());
-        ProgramCommitment { exe, vm }
+        self.app_commitment
     }
 
     /// Get the SDK for this prover.
-    pub fn sdk(&self) -> Result<&Sdk, Error> {
+    pub fn sdk(&self) -> Result<Arc<Sdk>, Error> {
         self.get_sdk()
     }
 
@@ -153,9 +511,7 @@ impl Prover {
     /// of proving keys to GPU memory that is never reclaimed.
     ///
     /// Falls back to deriving the VK from the SDK if the asset is missing.
-    pub fn load_agg_vk(
-        &self,
-    ) -> Result<Arc<openvm_stark_backend::keygen::types::MultiStarkVerifyingKey<SC>>, Error> {
+    pub fn load_agg_vk(&self) -> Result<Arc<MultiStarkVerifyingKey<SC>>, Error> {
         let path = self.config.path_app_exe.with_file_name(Self::FD_AGG_VK);
         match openvm_sdk::fs::read_object_from_file(&path) {
             Ok(mvk) => Ok(Arc::new(mvk)),
@@ -174,18 +530,21 @@ impl Prover {
         serialize_vk::serialize(&self.get_app_commitment())
     }
 
-    /// Pick up the actual vk (serialized) for evm proof, would be empty if prover
-    /// do not contain evm prover
-    pub fn get_evm_vk(&self) -> Vec<u8> {
-        let sdk = self.get_sdk().expect("Failed to initialize SDK");
-        scroll_zkvm_verifier::evm::serialize_vk(sdk.halo2_pk().wrapper.pinning.pk.get_vk())
+    /// Pick up the actual vk (serialized) for evm proof, failing if the SDK, and so the halo2
+    /// keys, cannot be set up.
+    pub fn get_evm_vk(&self) -> Result<Vec<u8>, Error> {
+        let sdk = self.get_sdk()?;
+        let vk =
+            scroll_zkvm_verifier::evm::serialize_vk(sdk.halo2_pk().wrapper.pinning.pk.get_vk());
+        self.persist_keys(&sdk, &[KeyKind::App, KeyKind::Agg, KeyKind::Halo2]);
+        Ok(vk)
     }
 
     /// Enable deferred STARK verification by configuring this prover's SDK
     /// to use the child prover's aggregation VK for deferral proof generation.
     ///
-    /// This method pre-builds the SDK with deferral enabled. After calling this,
-    /// `get_sdk()` will return the deferral-enabled SDK directly.
+    /// Only the app config is extended with deferral here. The SDK, and so the proving keys, are
+    /// built with deferral by the first task, and rebuilt with it after a [`Prover::reset`].
     ///
     /// # Why deferral is needed
     ///
@@ -198,19 +557,68 @@ impl Prover {
     /// 2. A `deferral` extension injected into the VM config.
     /// 3. Extra memory space (`DEFERRAL_AS`) reserved for deferral state.
     pub fn enable_deferral(&mut self, child_prover: &Prover) -> Result<(), Error> {
-        let child_sdk = child_prover.get_sdk()?;
+        // Load the child agg VK from the pre-built asset when available, so we
+        // don't construct the child's (GPU) aggregation prover just to get the VK.
+        let child_agg_vk = child_prover.load_agg_vk()?;
+        let child_def_hook_commit = child_prover.def_hook_commit()?;
+        self.setup_deferral(child_agg_vk, child_def_hook_commit)
+    }
+
+    /// Same as [`Prover::enable_deferral`], reading what is needed of the child circuit from
+    /// its release assets in `dir_child`, i.e. its `agg_vk.bin` and `def_hook_commit.json`,
+    /// instead of a child prover.
+    ///
+    /// This allows e.g. a bundle prover to run on a machine without the batch circuit.
+    pub fn enable_deferral_from_assets<P: AsRef<Path>>(
+        &mut self,
+        dir_child: P,
+    ) -> Result<(), Error> {
+        let dir_child = dir_child.as_ref();
+        let path_agg_vk = dir_child.join(Self::FD_AGG_VK);
+        let child_agg_vk: MultiStarkVerifyingKey<SC> =
+            openvm_sdk::fs::read_object_from_file(&path_agg_vk).map_err(|e| Error::Setup {
+                path: path_agg_vk,
+                src: e.to_string(),
+            })?;
+        let child_def_hook_commit =
+            read_def_hook_commit(dir_child)?.ok_or_else(|| Error::Setup {
+                path: dir_child.join(FD_DEF_HOOK_COMMIT),
+                src: "missing, the release predates it".to_string(),
+            })?;
+        self.setup_deferral(Arc::new(child_agg_vk), child_def_hook_commit)
+    }
+
+    /// The deferral hook commit of this circuit, verified by the circuit aggregating its
+    /// proofs. `None` without deferral.
+    ///
+    /// Read from the asset written by build-guest next to `app.vmexe` when available, so that
+    /// the SDK is not constructed just to get the commit.
+    pub fn def_hook_commit(&self) -> Result<Option<CommitBytes>, Error> {
+        if self.deferral.is_none() {
+            return Ok(None);
+        }
+        let dir_assets = self.config.path_app_exe.parent().unwrap_or(Path::new(""));
+        match read_def_hook_commit(dir_assets)? {
+            Some(commit) => Ok(commit),
+            None => Ok(self.get_sdk()?.def_hook_commit().map(|c| c.into())),
+        }
+    }
+
+    fn setup_deferral(
+        &mut self,
+        child_agg_vk: Arc<MultiStarkVerifyingKey<SC>>,
+        child_def_hook_commit: Option<CommitBytes>,
+    ) -> Result<(), Error> {
         // Build the verify-stark deferral circuit from the child's full aggregation
         // VK (root verifier VK). The cached commit must be the child's
         // internal-recursive VK commit, computed with commit_child_vk so it matches
         // what the child root proof exposes.
-        // Load the child agg VK from the pre-built asset when available, so we
-        // don't construct the child's (GPU) aggregation prover just to get the VK.
-        let child_agg_vk = child_prover.load_agg_vk()?;
         let engine = <DeferralEngine as StarkEngine>::new(child_agg_vk.inner.params.clone());
         let internal_recursive_cached_commit: CommitBytes =
             commit_child_vk(&engine, &child_agg_vk, true)
                 .commitment
                 .into();
+        let app_config_before = toml::to_string(&self.app_config).ok();
 
         // Reserve deferral address space in the parent config *before* reading the
         // memory dimensions used by the deferral circuit, so the circuit's layout
@@ -231,6 +639,40 @@ impl Prover {
         }
         addr_spaces[deferral_as].num_cells = 1 << 25;
 
+        let deferral = DeferralSetup {
+            child_agg_vk,
+            internal_recursive_cached_commit,
+            child_def_hook_commit,
+            agg_prover: Mutex::new(None),
+        };
+        let deferral_agg_prover = self.deferral_agg_prover(&deferral)?;
+        let deferral_config = deferral_agg_prover
+            .multi_deferral_circuit_prover
+            .make_config(vec![SupportedDeferral::VerifyStark]);
+
+        self.app_config.app_vm_config.deferral = Some(deferral_config);
+        // An SDK built before, e.g. to read the vks, lacks deferral.
+        *self.sdk.get_mut().unwrap_or_else(|e| e.into_inner()) = None;
+        // The app proving key of the setup is that of the config read from `openvm.toml`, which
+        // build-guest writes with deferral already.
+        let app_config_after = toml::to_string(&self.app_config).ok();
+        if app_config_before.is_none() || app_config_before != app_config_after {
+            *self
+                .setup_app_pk
+                .get_mut()
+                .unwrap_or_else(|e| e.into_inner()) = None;
+        }
+        *deferral
+            .agg_prover
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(deferral_agg_prover);
+        self.deferral = Some(deferral);
+
+        Ok(())
+    }
+
+    /// Build the deferral prover verifying the STARK proofs of the child circuit.
+    fn deferral_agg_prover(&self, deferral: &DeferralSetup) -> Result<DeferralAggProver, Error> {
         // The verify-stark deferral circuit runs as part of *this* (parent) VM, so
         // its memory layout and public-value count must match the parent's config.
         let system_config = self.app_config.app_vm_config.as_ref().clone();
@@ -238,85 +680,219 @@ impl Prover {
         let num_user_pvs = system_config.num_public_values;
 
         let def_circuit_params = internal_params_with_100_bits_security();
-        let child_def_hook_commit: Option<CommitBytes> =
-            child_sdk.def_hook_commit().map(|c| c.into());
         let deferred_verify_prover = VerifyProver::new::<DeferralEngine>(
-            child_agg_vk,
-            internal_recursive_cached_commit,
+            deferral.child_agg_vk.clone(),
+            deferral.internal_recursive_cached_commit.clone(),
             def_circuit_params,
             memory_dimensions,
             num_user_pvs,
-            child_def_hook_commit,
+            deferral.child_def_hook_commit.clone(),
             0,
         );
         let verify_stark_prover = VerifyCircuitProver::new(deferred_verify_prover);
 
         let hook_params = hook_params_with_100_bits_security();
         let agg_config = AggregationConfig {
-            params: default_agg_params(),
+            params: self.agg_params()?,
         };
         let multi_deferral_circuit_prover =
             MultiDeferralCircuitProver::new(verify_stark_prover, agg_config.clone(), hook_params);
-        let deferral_agg_prover =
-            DeferralAggProver::new(agg_config, Arc::new(multi_deferral_circuit_prover));
-        let deferral_config = deferral_agg_prover
-            .multi_deferral_circuit_prover
-            .make_config(vec![SupportedDeferral::VerifyStark]);
-
-        self.app_config.app_vm_config.deferral = Some(deferral_config);
-
-        // Pre-build SDK with deferral enabled so get_sdk() returns it directly.
-        self.reset();
-        let sdk = Sdk::builder()
-            .app_config(self.app_config.clone())
-            .agg_params(default_agg_params())
-            .agg_tree_config(DEFAULT_AGG_TREE_CONFIG)
-            .deferral_agg_prover(deferral_agg_prover)
-            .build()
-            .map_err(|e| Error::GenProof(e.to_string()))?;
-        self.sdk
-            .set(sdk)
-            .map_err(|_| Error::GenProof("sdk already set".to_string()))?;
+        Ok(DeferralAggProver::new(
+            agg_config,
+            Arc::new(multi_deferral_circuit_prover),
+        ))
+    }
 
-        Ok(())
+    /// Check upfront that `task` is meant for this prover, so that a misrouted task is rejected
+    /// instead of failing deep into proving.
+    ///
+    /// - The vk of the task, if specified, must match [`Prover::get_app_vk`].
+    /// - The fork of the task must be one of [`ProverConfig::forks`], if specified.
+    /// - The version byte leading the witness must be a known version of the task's fork.
+    pub fn admit(&self, task: &impl ProvingTask) -> Result<(), Error> {
+        check_admission(task, &self.config.forks, &self.get_app_vk())
     }
 
-    /// Simple wrapper of gen_proof_stark/snark, Early-return if a proof is found in disc,
-    /// otherwise generate and return the proof after writing to disc.
+    /// Simple wrapper of gen_proof_stark/snark, Early-return if a proof is found in the
+    /// [`ProofStore`], otherwise generate and return the proof after writing to the store.
+    ///
+    /// The task is checked by [`Prover::admit`] first.
     #[instrument("Prover::gen_proof_universal", skip_all, fields(task_id))]
     pub fn gen_proof_universal(
-        &mut self,
+        &self,
         task: &impl ProvingTask,
         with_snark: bool,
+    ) -> Result<ProofEnum, Error> {
+        self.gen_proof_universal_with_control(task, with_snark, &ProvingControl::default())
+    }
+
+    /// Same as [`Prover::gen_proof_universal`], reporting progress to and honouring cancellation
+    /// through `control`.
+    ///
+    /// The SDK is released if the proof is cancelled, see [`Prover::reset`].
+    pub fn gen_proof_universal_with_control(
+        &self,
+        task: &impl ProvingTask,
+        with_snark: bool,
+        control: &ProvingControl,
+    ) -> Result<ProofEnum, Error> {
+        self.prove_task(task, None, with_snark, control)
+    }
+
+    /// Prove `task`, checked by [`Prover::admit`] and looked up in and persisted to the
+    /// [`ProofStore`]. The `deferral` data is that of the child proofs of aggregation tasks.
+    fn prove_task(
+        &self,
+        task: &impl ProvingTask,
+        deferral: Option<&DeferralData>,
+        with_snark: bool,
+        control: &ProvingControl,
     ) -> Result<ProofEnum, Error> {
         let task_id = task.identifier();
         tracing::debug!(name: "generate_root_verifier_input", task_id);
 
-        let stdin = task.build_guest_input();
+        self.admit(task)?;
+
+        let store_key = self.proof_store.as_ref().map(|_| ProofKey {
+            task_id: task_id.clone(),
+            app_vk: self.get_app_vk(),
+            fork_name: task.fork_name().as_str().to_string(),
+            witness_hash: task.witness_hash(),
+            kind: if with_snark {
+                ProofKind::Evm
+            } else {
+                ProofKind::Stark
+            },
+        });
+
+        // Early-return if the proof was already generated.
+        if let (Some(store), Some(key)) = (&self.proof_store, &store_key) {
+            match store.get(key) {
+                Ok(Some(proof)) => {
+                    tracing::info!(
+                        "found proof in store for {}, task id {task_id}, isevm {with_snark}",
+                        self.prover_name
+                    );
+                    return Ok(proof);
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("failed to read proof from store, re-proving: {e}"),
+            }
+        }
+
+        let mut stdin = task.build_guest_input();
+        let def_inputs = match deferral {
+            Some(deferral) => {
+                stdin.deferrals = deferral.states.clone();
+                deferral.inputs.as_slice()
+            }
+            None => &[],
+        };
+
+        // Proving consumes the stdin, keep it for the crash bundle.
+        let crash_stdin = self.config.dir_crash.as_ref().map(|_| stdin.clone());
+        let guest_output = GuestOutput::default();
 
         // Generate a new proof.
-        let proof = if !with_snark {
-            self.gen_proof_stark(stdin, &[])?.into()
+        let result: Result<ProofEnum, Error> = if !with_snark {
+            let result = self.prove_stark(stdin, def_inputs, control, &guest_output);
+            self.reset_if_cancelled(result).map(Into::into)
         } else {
-            EvmProof::from(self.gen_proof_snark(stdin, &[])?).into()
+            let result = self.prove_snark(stdin, def_inputs, control, &guest_output);
+            self.reset_if_cancelled(result).map(|(evm_proof, stat)| {
+                EvmProof {
+                    stat: Some(stat),
+                    ..EvmProof::from(evm_proof)
+                }
+                .into()
+            })
         };
+        let proof = result.inspect_err(|e| {
+            if let Some(stdin) = &crash_stdin {
+                self.write_crash_bundle(task, stdin, def_inputs, e, &guest_output);
+            }
+        })?;
 
         tracing::info!(
             "app proof generated for {}, task id {task_id}, isevm {with_snark}",
             self.prover_name
         );
+
+        // A failure to persist the proof should not throw away the proof itself.
+        if let (Some(store), Some(key)) = (&self.proof_store, &store_key) {
+            if let Err(e) = store.put(key, &proof) {
+                tracing::warn!("failed to write proof to store: {e}");
+            }
+        }
+
         Ok(proof)
     }
 
+    /// Write a crash bundle for `task` failing with `error`, if configured, see
+    /// [`ProverConfig::dir_crash`]. The bundle holds the `guest_output` of the failed run, and
+    /// the `def_inputs` of aggregation tasks to prove the task again.
+    ///
+    /// Cancellations are not failures of the task, so get no bundle. A failure to write the
+    /// bundle must not hide the error of the task, so it is logged.
+    fn write_crash_bundle(
+        &self,
+        task: &impl ProvingTask,
+        stdin: &StdIn,
+        def_inputs: &[openvm_sdk::DeferralInput],
+        error: &Error,
+        guest_output: &GuestOutput,
+    ) {
+        let Some(dir_crash) = &self.config.dir_crash else {
+            return;
+        };
+        if matches!(error, Error::Cancelled) {
+            return;
+        }
+        let info = CrashInfo {
+            prover_name: self.prover_name.clone(),
+            task_id: task.identifier(),
+            fork_name: task.fork_name().as_str().to_string(),
+            error: error.to_string(),
+            app_vk: hex::encode(self.get_app_vk()),
+            path_app_exe: self.config.path_app_exe.clone(),
+            path_app_config: self.config.path_app_config.clone(),
+            prover_version: crate::utils::GIT_VERSION.to_string(),
+            guest_release: self.config.release.clone().unwrap_or_default(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+        };
+        match crate::crash::write_bundle(
+            dir_crash,
+            &info,
+            task,
+            stdin,
+            def_inputs,
+            &guest_output.text(),
+        ) {
+            Ok(dir) => tracing::warn!(
+                "task {} failed on {}, crash bundle written to {}",
+                info.task_id,
+                self.prover_name,
+                dir.display()
+            ),
+            Err(e) => tracing::warn!("failed to write the crash bundle: {e}"),
+        }
+    }
+
     /// Execute the guest program to get the cycle count.
     pub fn execute_and_check_with_full_result(
         &self,
         stdin: &StdIn,
-    ) -> Result<crate::utils::vm::ExecutionResult, Error> {
+    ) -> Result<ExecutionResult, Error> {
         let sdk = self.get_sdk()?;
         let t = std::time::Instant::now();
-        let exec_result = crate::utils::vm::execute_guest(sdk, self.app_exe.clone(), stdin)
-            .map_err(|e| Error::GenProof(e.to_string()))?;
+        let exec_result = crate::utils::vm::execute_guest(
+            &sdk,
+            self.app_exe.clone(),
+            stdin,
+            &GuestOutput::default(),
+        )?;
         let execution_time_mills = t.elapsed().as_millis() as u64;
         let execution_time_s = execution_time_mills as f32 / 1000.0f32;
         let exec_speed = (exec_result.total_cycle as f32 / 1_000_000.0f32) / execution_time_s; // MHz
@@ -327,9 +903,94 @@ impl Prover {
             exec_speed,
             execution_time_s
         );
+        if let Some(metering_failure) = &exec_result.metering_failure {
+            tracing::info!(
+                "cycles of {} counted by segmented execution, metering failed: {metering_failure}",
+                self.prover_name
+            );
+        }
         Ok(exec_result)
     }
 
+    /// Execute the task without proving it, reporting the segmentation, the execution time and
+    /// the pi_hash revealed by the guest.
+    ///
+    /// This is cheap compared to proving and catches most invalid tasks upfront. The task is
+    /// checked by [`Prover::admit`] first.
+    #[instrument("Prover::dry_run", skip_all, fields(task_id = task.identifier()))]
+    pub fn dry_run(&self, task: &impl ProvingTask) -> Result<DryRunReport, Error> {
+        self.dry_run_task(task, None)
+    }
+
+    /// Dry run `task`, see [`Prover::dry_run`]. The `deferral` data is that of the child proofs
+    /// of aggregation tasks.
+    fn dry_run_task(
+        &self,
+        task: &impl ProvingTask,
+        deferral: Option<&DeferralData>,
+    ) -> Result<DryRunReport, Error> {
+        self.admit(task)?;
+        let mut stdin = task.build_guest_input();
+        let def_inputs = match deferral {
+            Some(deferral) => {
+                stdin.deferrals = deferral.states.clone();
+                deferral.inputs.as_slice()
+            }
+            None => &[],
+        };
+        let sdk = self.get_sdk()?;
+        let t = std::time::Instant::now();
+        let guest_output = GuestOutput::default();
+        let exec_result = crate::utils::vm::execute_guest_segmented(
+            &sdk,
+            self.app_exe.clone(),
+            &stdin,
+            &guest_output,
+        )
+        .inspect_err(|e| self.write_crash_bundle(task, &stdin, def_inputs, e, &guest_output))?;
+        let execution_time_mills = t.elapsed().as_millis() as u64;
+        let pi_hash = pi_hash_from_public_values(&exec_result.public_values)?;
+        tracing::info!(
+            "dry run of {}: {} segments, total cycle {}, exec time: {:.2}s",
+            self.prover_name,
+            exec_result.segments.len(),
+            exec_result.total_cycle(),
+            execution_time_mills as f32 / 1000.0f32,
+        );
+
+        Ok(DryRunReport {
+            num_segments: exec_result.segments.len(),
+            total_cycles: exec_result.total_cycle(),
+            segment_cycles: exec_result.segment_cycles(),
+            execution_time_mills,
+            pi_hash,
+            #[cfg(feature = "scroll")]
+            chunk: None,
+        })
+    }
+
+    /// Same as [`Prover::dry_run`] for a chunk task, additionally computing the
+    /// [`ChunkInfo`][scroll_zkvm_types::scroll::chunk::ChunkInfo] natively and checking its
+    /// `pi_hash_by_version` against the pi_hash revealed by the guest.
+    #[cfg(feature = "scroll")]
+    pub fn dry_run_chunk(
+        &self,
+        task: &scroll_zkvm_types::task::ProvingTask,
+    ) -> Result<DryRunReport, Error> {
+        let mut report = self.dry_run(task)?;
+        let chunk = crate::dry_run::ChunkCheck::new(task, &report.pi_hash)?;
+        if !chunk.pi_hash_matches {
+            tracing::warn!(
+                "pi_hash mismatch for chunk task {}: guest 0x{}, host 0x{}",
+                task.identifier,
+                hex::encode(report.pi_hash),
+                hex::encode(chunk.expected_pi_hash),
+            );
+        }
+        report.chunk = Some(chunk);
+        Ok(report)
+    }
+
     /// Execute the guest program to get the cycle count.
     pub fn execute_and_check(&self, stdin: &StdIn) -> Result<u64, Error> {
         self.execute_and_check_with_full_result(stdin)
@@ -344,18 +1005,137 @@ impl Prover {
         stdin: StdIn,
         def_inputs: &[openvm_sdk::DeferralInput],
     ) -> Result<StarkProof, Error> {
-        // Here we always do an execution of the guest program to get the cycle count.
-        // and do precheck before proving like ensure PI != 0
+        self.prove_stark(
+            stdin,
+            def_inputs,
+            &ProvingControl::default(),
+            &GuestOutput::default(),
+        )
+    }
+
+    /// Same as [`Prover::gen_proof_stark`], reporting progress to and honouring cancellation
+    /// through `control`.
+    ///
+    /// The SDK is released if the proof is cancelled, see [`Prover::reset`].
+    pub fn gen_proof_stark_with_control(
+        &self,
+        stdin: StdIn,
+        def_inputs: &[openvm_sdk::DeferralInput],
+        control: &ProvingControl,
+    ) -> Result<StarkProof, Error> {
+        let result = self.prove_stark(stdin, def_inputs, control, &GuestOutput::default());
+        self.reset_if_cancelled(result)
+    }
+
+    /// Release the SDK if `result` is a cancellation, so that a cancelled proof does not keep
+    /// holding the (GPU) memory of the proving keys.
+    fn reset_if_cancelled<T>(&self, result: Result<T, Error>) -> Result<T, Error> {
+        if matches!(result, Err(Error::Cancelled)) {
+            tracing::info!("proof cancelled for {}, releasing SDK", self.prover_name);
+            self.reset();
+        }
+        result
+    }
+
+    /// Execute the guest program under `control`, reporting [`ProvingPhase::MeteredExecution`].
+    ///
+    /// The execution is segmented, the segments are those proven by the app prover, see
+    /// [`Prover::prove_vm_stark`]. What the guest prints is written to `guest_output`.
+    fn execute_with_control(
+        &self,
+        stdin: &StdIn,
+        control: &ProvingControl,
+        guest_output: &GuestOutput,
+    ) -> Result<SegmentedExecutionResult, Error> {
+        let sdk = self.get_sdk()?;
+        let exec_result = control.run(ProvingPhase::MeteredExecution, || {
+            crate::utils::vm::execute_guest_segmented(
+                &sdk,
+                self.app_exe.clone(),
+                stdin,
+                guest_output,
+            )
+        })??;
+        tracing::info!(
+            "total cycle of {}: {}, segments: {}",
+            self.prover_name,
+            exec_result.total_cycle(),
+            exec_result.segments.len(),
+        );
+        Ok(exec_result)
+    }
+
+    /// Stat of a proof whose metered execution took `execution_time_mills` and whose proving
+    /// pass, spending `preflight_time_mills` executing the segments again, started at
+    /// `proving_start` and ended now.
+    fn stat(
+        &self,
+        control: &ProvingControl,
+        exec_result: &SegmentedExecutionResult,
+        execution_time_mills: u64,
+        preflight_time_mills: u64,
+        proving_start: std::time::Instant,
+    ) -> StarkProofStat {
+        let proving_end = std::time::Instant::now();
+        StarkProofStat {
+            total_cycles: exec_result.total_cycle(),
+            execution_time_mills,
+            preflight_time_mills,
+            proving_time_mills: proving_end
+                .saturating_duration_since(proving_start)
+                .as_millis() as u64,
+            phase_time_mills: control.phase_times(proving_end),
+            process_peak_rss_bytes: crate::utils::process_peak_rss_bytes().unwrap_or_default(),
+            prover_version: crate::utils::short_git_version(),
+            guest_release: self.config.release.clone().unwrap_or_default(),
+            num_segments: exec_result.segments.len() as u64,
+        }
+    }
+
+    fn prove_stark(
+        &self,
+        stdin: StdIn,
+        def_inputs: &[openvm_sdk::DeferralInput],
+        control: &ProvingControl,
+        guest_output: &GuestOutput,
+    ) -> Result<StarkProof, Error> {
+        // Here we always do an execution of the guest program to get the cycle count and the
+        // segments to prove, and do precheck before proving like ensure the guest does not panic
         let t = std::time::Instant::now();
-        let total_cycles = self.execute_and_check(&stdin)?;
+        let exec_result = self.execute_with_control(&stdin, control, guest_output)?;
+        let total_cycles = exec_result.total_cycle();
         let execution_time_mills = t.elapsed().as_millis() as u64;
 
         let t = std::time::Instant::now();
         let sdk = self.get_sdk()?;
-        let (vm_stark_proof, baseline) = sdk
-            .prove(self.app_exe.clone(), stdin, def_inputs)
-            .map_err(|e| Error::GenProof(e.to_string()))?;
+        let VmStarkOutput {
+            proof: vm_stark_proof,
+            baseline,
+            preflight_time_mills,
+        } = self.prove_vm_stark(&sdk, stdin, &exec_result.segments, def_inputs, control)?;
         let proving_time_mills = t.elapsed().as_millis() as u64;
+        let stat = self.stat(
+            control,
+            &exec_result,
+            execution_time_mills,
+            preflight_time_mills,
+            t,
+        );
+
+        // The proving pass must reveal what the metered execution did.
+        let proven_public_values: Vec<u8> = vm_stark_proof
+            .user_pvs_proof
+            .public_values
+            .iter()
+            .map(|x| x.as_canonical_u32() as u8)
+            .collect();
+        if !proven_public_values.starts_with(&exec_result.public_values) {
+            return Err(Error::SelfVerification(format!(
+                "public values of the proof 0x{} differ from the executed 0x{}",
+                hex::encode(&proven_public_values),
+                hex::encode(&exec_result.public_values),
+            )));
+        }
         let proving_time_s = proving_time_mills as f32 / 1000.0f32;
         let prove_speed = (total_cycles as f32 / 1_000_000.0f32) / proving_time_s; // MHz
         tracing::info!(
@@ -365,12 +1145,6 @@ impl Prover {
             proving_time_s
         );
 
-        let stat = StarkProofStat {
-            total_cycles,
-            proving_time_mills,
-            execution_time_mills,
-        };
-
         // Encode the inner proof
         let proof_bytes = vm_stark_proof
             .inner
@@ -406,9 +1180,15 @@ impl Prover {
 
         tracing::info!("verifing stark proof");
         let agg_vk = self.get_sdk()?.agg_vk();
-        UniversalVerifier::verify_stark_proof_with_vk(&agg_vk, &proof, &self.get_app_vk())
-            .map_err(|e| Error::VerifyProof(e.to_string()))?;
+        UniversalVerifier::verify_stark_proof_strict_with_vk(
+            &agg_vk,
+            &proof,
+            &self.get_app_vk(),
+            self.def_hook_commit()?,
+        )
+        .map_err(|e| Error::SelfVerification(e.to_string()))?;
         tracing::info!("verifing stark proof done");
+        self.persist_keys(&sdk, &[KeyKind::App, KeyKind::Agg]);
         Ok(proof)
     }
 
@@ -421,13 +1201,272 @@ impl Prover {
         stdin: StdIn,
         def_inputs: &[openvm_sdk::DeferralInput],
     ) -> Result<OpenVmEvmProof, Error> {
-        self.execute_and_check(&stdin)?;
+        self.prove_snark(
+            stdin,
+            def_inputs,
+            &ProvingControl::default(),
+            &GuestOutput::default(),
+        )
+        .map(|(evm_proof, _)| evm_proof)
+    }
+
+    /// Same as [`Prover::gen_proof_snark`], reporting progress to and honouring cancellation
+    /// through `control`.
+    ///
+    /// The SDK is released if the proof is cancelled, see [`Prover::reset`].
+    pub fn gen_proof_snark_with_control(
+        &self,
+        stdin: StdIn,
+        def_inputs: &[openvm_sdk::DeferralInput],
+        control: &ProvingControl,
+    ) -> Result<OpenVmEvmProof, Error> {
+        let result = self.prove_snark(stdin, def_inputs, control, &GuestOutput::default());
+        self.reset_if_cancelled(result)
+            .map(|(evm_proof, _)| evm_proof)
+    }
+
+    fn prove_snark(
+        &self,
+        stdin: StdIn,
+        def_inputs: &[openvm_sdk::DeferralInput],
+        control: &ProvingControl,
+        guest_output: &GuestOutput,
+    ) -> Result<(OpenVmEvmProof, StarkProofStat), Error> {
+        let t = std::time::Instant::now();
+        let exec_result = self.execute_with_control(&stdin, control, guest_output)?;
+        let execution_time_mills = t.elapsed().as_millis() as u64;
+
+        let t = std::time::Instant::now();
+        let sdk = self.get_sdk()?;
+        let VmStarkOutput {
+            proof: vm_stark_proof,
+            baseline,
+            preflight_time_mills,
+        } = self.prove_vm_stark(&sdk, stdin, &exec_result.segments, def_inputs, control)?;
+        let evm_proof = control
+            .run(ProvingPhase::Halo2, || {
+                sdk.prove_evm_from_stark(vm_stark_proof, baseline)
+            })?
+            .map_err(Error::from_sdk)?;
+        let stat = self.stat(
+            control,
+            &exec_result,
+            execution_time_mills,
+            preflight_time_mills,
+            t,
+        );
+        self.persist_keys(&sdk, &[KeyKind::App, KeyKind::Agg, KeyKind::Halo2]);
 
+        Ok((evm_proof, stat))
+    }
+
+    /// Wrap a STARK proof generated by this prover, e.g. a bundle proof, into an [`EvmProof`].
+    ///
+    /// Only the root and halo2 proofs are generated, the guest is neither executed nor proven
+    /// again. This allows wrapping on another machine than the one generating the STARK proof,
+    /// and retrying a failed wrap cheaply. The STARK proof is verified against the app vk of
+    /// this prover first.
+    pub fn wrap_to_evm(&self, proof: &StarkProof) -> Result<EvmProof, Error> {
+        self.prove_wrap(proof, &ProvingControl::default())
+    }
+
+    /// Same as [`Prover::wrap_to_evm`], reporting progress to and honouring cancellation
+    /// through `control`.
+    ///
+    /// The SDK is released if the proof is cancelled, see [`Prover::reset`].
+    pub fn wrap_to_evm_with_control(
+        &self,
+        proof: &StarkProof,
+        control: &ProvingControl,
+    ) -> Result<EvmProof, Error> {
+        let result = self.prove_wrap(proof, control);
+        self.reset_if_cancelled(result)
+    }
+
+    fn prove_wrap(&self, proof: &StarkProof, control: &ProvingControl) -> Result<EvmProof, Error> {
         let sdk = self.get_sdk()?;
-        let evm_proof = sdk
-            .prove_evm(self.app_exe.clone(), stdin, def_inputs)
-            .map_err(|e| Error::GenProof(format!("{}", e)))?;
+        // The proof may come from elsewhere, so its baseline is not trusted.
+        UniversalVerifier::verify_stark_proof_strict_with_vk(
+            &sdk.agg_vk(),
+            proof,
+            &self.get_app_vk(),
+            self.def_hook_commit()?,
+        )
+        .map_err(|e| Error::VerifyProof(e.to_string()))?;
+        let (vm_stark_proof, baseline) = crate::utils::vm::decode_stark_proof(proof)?;
+
+        let t = std::time::Instant::now();
+        let evm_proof = control
+            .run(ProvingPhase::Halo2, || {
+                sdk.prove_evm_from_stark(vm_stark_proof, baseline)
+            })?
+            .map_err(Error::from_sdk)?;
+        let proving_end = std::time::Instant::now();
+        tracing::info!(
+            "{} wrapped stark proof in {:.2}s",
+            self.prover_name,
+            proving_end.saturating_duration_since(t).as_secs_f32()
+        );
+
+        // Execution and app proving took place when generating the STARK proof.
+        let stat = StarkProofStat {
+            total_cycles: proof.stat.total_cycles,
+            num_segments: proof.stat.num_segments,
+            proving_time_mills: proving_end.saturating_duration_since(t).as_millis() as u64,
+            phase_time_mills: control.phase_times(proving_end),
+            process_peak_rss_bytes: crate::utils::process_peak_rss_bytes().unwrap_or_default(),
+            prover_version: crate::utils::short_git_version(),
+            guest_release: self.config.release.clone().unwrap_or_default(),
+            ..Default::default()
+        };
 
-        Ok(evm_proof)
+        self.persist_keys(&sdk, &[KeyKind::App, KeyKind::Agg, KeyKind::Halo2]);
+        Ok(EvmProof {
+            stat: Some(stat),
+            ..EvmProof::from(evm_proof)
+        })
+    }
+}
+
+/// Read the deferral hook commit recorded in `dir`, see [`FD_DEF_HOOK_COMMIT`]. `None` if not
+/// recorded.
+fn read_def_hook_commit(dir: &Path) -> Result<Option<Option<CommitBytes>>, Error> {
+    let path = dir.join(FD_DEF_HOOK_COMMIT);
+    if !path.exists() {
+        return Ok(None);
+    }
+    crate::utils::read_json(&path).map(Some)
+}
+
+/// The checks of [`Prover::admit`], for a prover of the `forks` with the app vk `app_vk`.
+fn check_admission(task: &impl ProvingTask, forks: &[String], app_vk: &[u8]) -> Result<(), Error> {
+    if !task.vk().is_empty() && task.vk() != app_vk {
+        return Err(AdmissionError::VkMismatch {
+            task: hex::encode(task.vk()),
+            loaded: hex::encode(app_vk),
+        }
+        .into());
+    }
+
+    let fork = task.try_fork_name()?;
+    if !forks.is_empty()
+        && !forks
+            .iter()
+            .any(|supported| supported.eq_ignore_ascii_case(fork.as_str()))
+    {
+        return Err(AdmissionError::UnsupportedFork {
+            fork: fork.as_str().to_string(),
+            supported: forks.to_vec(),
+        }
+        .into());
+    }
+
+    if let Some(version_byte) = task.witness_version()? {
+        let version = Version::try_from_version_byte(version_byte)
+            .ok_or(AdmissionError::UnknownVersion(version_byte))?;
+        if version.fork != fork {
+            return Err(AdmissionError::VersionForkMismatch {
+                version: version_byte,
+                witness_fork: version.fork.as_str().to_string(),
+                task_fork: fork.as_str().to_string(),
+            }
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use scroll_zkvm_types::task::ProvingTask as UniversalProvingTask;
+
+    use super::*;
+
+    const APP_VK: &[u8] = &[1, 2, 3];
+
+    fn task(fork_name: &str, version: Option<u8>, vk: &[u8]) -> UniversalProvingTask {
+        UniversalProvingTask {
+            serialized_witness: version
+                .map(|version| vec![version, 0, 0])
+                .into_iter()
+                .collect(),
+            aggregated_proofs: vec![],
+            fork_name: fork_name.to_string(),
+            vk: vk.to_vec(),
+            identifier: "task".to_string(),
+            input_commits: vec![],
+        }
+    }
+
+    fn admit(task: &UniversalProvingTask, forks: &[&str]) -> Result<(), AdmissionError> {
+        let forks: Vec<String> = forks.iter().map(|fork| fork.to_string()).collect();
+        match check_admission(task, &forks, APP_VK) {
+            Ok(()) => Ok(()),
+            Err(Error::Admission(e)) => Err(e),
+            Err(e) => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn admit_matching_task() {
+        let galileo_v2 = Version::galileo_v2().as_version_byte();
+        assert_eq!(
+            admit(&task("galileov2", Some(galileo_v2), APP_VK), &[]),
+            Ok(())
+        );
+        assert_eq!(
+            admit(&task("galileov2", Some(galileo_v2), &[]), &["GalileoV2"]),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn reject_vk_mismatch() {
+        let galileo_v2 = Version::galileo_v2().as_version_byte();
+        assert_eq!(
+            admit(&task("galileov2", Some(galileo_v2), &[4]), &[]),
+            Err(AdmissionError::VkMismatch {
+                task: "04".to_string(),
+                loaded: "010203".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn reject_fork() {
+        let feynman = Version::feynman().as_version_byte();
+        assert_eq!(
+            admit(&task("osaka", Some(feynman), APP_VK), &[]),
+            Err(AdmissionError::UnknownFork("osaka".to_string()))
+        );
+        assert_eq!(
+            admit(&task("feynman", Some(feynman), APP_VK), &["galileov2"]),
+            Err(AdmissionError::UnsupportedFork {
+                fork: "feynman".to_string(),
+                supported: vec!["galileov2".to_string()],
+            })
+        );
+    }
+
+    #[test]
+    fn reject_witness_version() {
+        let feynman = Version::feynman().as_version_byte();
+        assert_eq!(
+            admit(&task("galileov2", None, APP_VK), &[]),
+            Err(AdmissionError::MissingWitness)
+        );
+        assert_eq!(
+            admit(&task("galileov2", Some(0xff), APP_VK), &[]),
+            Err(AdmissionError::UnknownVersion(0xff))
+        );
+        assert_eq!(
+            admit(&task("galileov2", Some(feynman), APP_VK), &[]),
+            Err(AdmissionError::VersionForkMismatch {
+                version: feynman,
+                witness_fork: "feynman".to_string(),
+                task_fork: "galileov2".to_string(),
+            })
+        );
     }
 }
@@ -0,0 +1,105 @@
+//! Proving the guest program up to the root proof, one SDK step at a time: each app segment,
+//! then each layer of the aggregation tree. Cancellation is checked and progress reported
+//! between the steps, see [`ProvingControl::run`].
+
+use std::time::{Duration, Instant};
+
+use openvm_circuit::arch::execution_mode::Segment;
+use openvm_sdk::{DeferralInput, Sdk, StdIn, prover::vm::ContinuationVmProof};
+use openvm_verify_stark_host::{VmStarkProof, vk::VerificationBaseline};
+
+use super::Prover;
+use crate::{
+    Error,
+    progress::{ProvingControl, ProvingPhase},
+};
+
+/// Root proof of the guest program, see [`Prover::prove_vm_stark`].
+pub(super) struct VmStarkOutput {
+    pub proof: VmStarkProof,
+    pub baseline: VerificationBaseline,
+    /// Time spent executing the app segments again to record their traces.
+    pub preflight_time_mills: u64,
+}
+
+impl Prover {
+    /// Prove the execution of the guest program on `stdin`, split into `segments` by the metered
+    /// execution, up to the root proof.
+    ///
+    /// The app segments are proven one by one from the state the previous one ended in, then the
+    /// app proofs are aggregated by the leaf layer, the internal layers until a single proof is
+    /// left, and the root layer, which also verifies the `def_inputs` of aggregation tasks.
+    ///
+    /// The guest is not executed as a whole again: each segment is only executed from its start
+    /// state to record the trace it is proven from, the time of which is reported.
+    pub(super) fn prove_vm_stark(
+        &self,
+        sdk: &Sdk,
+        stdin: StdIn,
+        segments: &[Segment],
+        def_inputs: &[DeferralInput],
+        control: &ProvingControl,
+    ) -> Result<VmStarkOutput, Error> {
+        let mut prover = sdk.prover(self.app_exe.clone()).map_err(Error::from_sdk)?;
+
+        let total = segments.len();
+        let mut state = prover
+            .app_prover
+            .initial_state(stdin)
+            .map_err(Error::from_sdk)?;
+        let mut per_segment = Vec::with_capacity(total);
+        let mut preflight_time = Duration::ZERO;
+        for (index, segment) in segments.iter().enumerate() {
+            let proof = control
+                .run(ProvingPhase::AppSegment { index, total }, || {
+                    let t = Instant::now();
+                    let ctx = prover.app_prover.execute_preflight(&mut state, segment)?;
+                    preflight_time += t.elapsed();
+                    prover.app_prover.prove_segment(ctx)
+                })?
+                .map_err(Error::from_sdk)?;
+            per_segment.push(proof);
+        }
+        let app_proof = ContinuationVmProof {
+            per_segment,
+            user_public_values: prover
+                .app_prover
+                .user_public_values_proof(&state)
+                .map_err(Error::from_sdk)?,
+        };
+
+        let mut proofs = control
+            .run(ProvingPhase::Leaf, || {
+                prover.agg_prover.generate_leaf_proofs(&app_proof)
+            })?
+            .map_err(Error::from_sdk)?;
+        // The leaf proofs are always aggregated by at least one internal layer.
+        let mut layer = 0;
+        while layer == 0 || proofs.len() > 1 {
+            proofs = control
+                .run(ProvingPhase::Internal { layer }, || {
+                    prover.agg_prover.generate_internal_proofs(proofs, layer)
+                })?
+                .map_err(Error::from_sdk)?;
+            layer += 1;
+        }
+        let internal_proof = proofs
+            .pop()
+            .ok_or_else(|| Error::GenProof("no internal proof to prove the root of".to_string()))?;
+
+        let (proof, baseline) = control
+            .run(ProvingPhase::Root, || {
+                prover.agg_prover.generate_root_proof(
+                    internal_proof,
+                    app_proof.user_public_values,
+                    def_inputs,
+                )
+            })?
+            .map_err(Error::from_sdk)?;
+        Ok(VmStarkOutput {
+            proof,
+            baseline,
+            preflight_time_mills: preflight_time.as_millis() as u64,
+        })
+    }
+}
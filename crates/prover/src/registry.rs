@@ -0,0 +1,329 @@
+//! Hosting several guest releases side by side, e.g. the releases before and after a hardfork,
+//! routing each task to the prover of its release by vk and fork.
+//!
+//! The circuits of a release are only loaded when a task is routed to the release, and are
+//! released again once idle for [`ProverRegistry::idle_timeout`]. The registry hands out shared
+//! handles to its provers, so that tasks routed concurrently are proven concurrently, and a
+//! release unloaded meanwhile is only dropped once its proofs in flight finish.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use scroll_zkvm_types::proof::ProofEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AdmissionError, Error, Prover,
+    circuits::{CircuitConfig, FD_APP_CONFIG, FD_APP_EXE, load_circuits},
+    progress::ProvingControl,
+    setup::{FD_RELEASE_VKS, compute_app_vk, read_release_vks},
+    task::ProvingTask,
+};
+
+/// A guest release to host in a [`ProverRegistry`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReleaseConfig {
+    /// Name of the release, e.g. `galileov2`.
+    pub name: String,
+    /// Forks served by the release. Empty to accept any fork.
+    #[serde(default)]
+    pub forks: Vec<String>,
+    /// Circuits of the release, children listed before the circuits aggregating them, see
+    /// [`load_circuits`].
+    pub circuits: Vec<CircuitConfig>,
+}
+
+impl ReleaseConfig {
+    /// Whether the release serves `fork`.
+    fn serves(&self, fork: &str) -> bool {
+        self.forks.is_empty()
+            || self
+                .forks
+                .iter()
+                .any(|supported| supported.eq_ignore_ascii_case(fork))
+    }
+}
+
+/// A release, and its circuits if loaded.
+struct Release {
+    config: ReleaseConfig,
+    /// App vk of each circuit, in the order of [`ReleaseConfig::circuits`].
+    vks: Vec<Vec<u8>>,
+    /// Whether the `vks` were read from [`FD_RELEASE_VKS`] rather than computed, in which case
+    /// they are checked against the computed ones when loading the release.
+    vks_recorded: bool,
+    state: Mutex<ReleaseState>,
+}
+
+/// The part of a [`Release`] changing as tasks are routed to it.
+struct ReleaseState {
+    /// The provers of the loaded circuits, in the order of [`ReleaseConfig::circuits`].
+    provers: Option<Vec<Arc<Prover>>>,
+    /// When a task was last routed to the release.
+    last_used: Instant,
+}
+
+impl Release {
+    fn state(&self) -> std::sync::MutexGuard<'_, ReleaseState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Provers of several guest releases, see the [module docs][self].
+pub struct ProverRegistry {
+    releases: Vec<Release>,
+    dir_cache: Option<PathBuf>,
+    dir_keys: Option<PathBuf>,
+    /// Duration after which a release no task was routed to is unloaded. `None` to keep
+    /// releases loaded.
+    pub idle_timeout: Option<Duration>,
+    /// Directory to write crash bundles in, see
+    /// [`ProverConfig::dir_crash`][crate::ProverConfig::dir_crash]. Applies to the releases
+    /// loaded afterwards.
+    pub dir_crash: Option<PathBuf>,
+}
+
+impl ProverRegistry {
+    /// Host `releases`, persisting proofs in `dir_cache/<release>/<circuit>` and caching the
+    /// proving keys in `dir_keys` if specified, see
+    /// [`ProverConfig::dir_keys`][crate::ProverConfig::dir_keys].
+    ///
+    /// The app vks of a release are read from `verifier/openVmVk.json` next to its circuits. A
+    /// release without that file has its vks computed from the `app.vmexe` and `openvm.toml` of
+    /// its circuits, see [`compute_app_vk`].
+    pub fn new(
+        releases: Vec<ReleaseConfig>,
+        dir_cache: Option<PathBuf>,
+        dir_keys: Option<PathBuf>,
+        idle_timeout: Option<Duration>,
+    ) -> Result<Self, Error> {
+        let mut registry = Self {
+            releases: Vec::with_capacity(releases.len()),
+            dir_cache,
+            dir_keys,
+            idle_timeout,
+            dir_crash: None,
+        };
+        for config in releases {
+            if registry
+                .releases
+                .iter()
+                .any(|r| r.config.name == config.name)
+            {
+                return Err(Error::Custom(format!(
+                    "release {} listed twice",
+                    config.name
+                )));
+            }
+            let recorded = read_circuit_vks(&config)?;
+            let vks_recorded = recorded.is_some();
+            let vks = match recorded {
+                Some(vks) => vks,
+                None => config
+                    .circuits
+                    .iter()
+                    .map(|circuit| {
+                        compute_app_vk(
+                            circuit.dir.join(FD_APP_EXE),
+                            circuit.dir.join(FD_APP_CONFIG),
+                        )
+                    })
+                    .collect::<Result<_, _>>()?,
+            };
+            let release = Release {
+                vks,
+                vks_recorded,
+                state: Mutex::new(ReleaseState {
+                    provers: None,
+                    last_used: Instant::now(),
+                }),
+                config,
+            };
+            for (circuit, vk) in release.config.circuits.iter().zip(&release.vks) {
+                tracing::info!(
+                    "registered circuit {} of release {}, vk {}",
+                    circuit.name,
+                    release.config.name,
+                    hex::encode(vk)
+                );
+            }
+            registry.releases.push(release);
+        }
+        Ok(registry)
+    }
+
+    /// The hosted circuits, as `(release, circuit, app vk)`.
+    pub fn circuits(&self) -> impl Iterator<Item = (&str, &str, &[u8])> {
+        self.releases.iter().flat_map(|release| {
+            release
+                .config
+                .circuits
+                .iter()
+                .zip(&release.vks)
+                .map(|(circuit, vk)| {
+                    (
+                        release.config.name.as_str(),
+                        circuit.name.as_str(),
+                        vk.as_slice(),
+                    )
+                })
+        })
+    }
+
+    /// Names of the releases currently loaded.
+    pub fn loaded(&self) -> impl Iterator<Item = &str> {
+        self.releases
+            .iter()
+            .filter(|release| release.state().provers.is_some())
+            .map(|release| release.config.name.as_str())
+    }
+
+    /// The prover of the circuit with the vk of `task`, in a release serving the fork of
+    /// `task`. The release is loaded if needed, and idle releases are unloaded.
+    ///
+    /// The prover is shared: it can prove while other tasks are routed, and outlives the
+    /// unloading of its release.
+    pub fn route(&self, task: &impl ProvingTask) -> Result<Arc<Prover>, Error> {
+        let fork = task.try_fork_name()?;
+        let vk = task.vk();
+        if vk.is_empty() {
+            return Err(AdmissionError::MissingVk.into());
+        }
+
+        let (i, j) = self
+            .releases
+            .iter()
+            .enumerate()
+            .filter(|(_, release)| release.config.serves(fork.as_str()))
+            .find_map(|(i, release)| {
+                release
+                    .vks
+                    .iter()
+                    .position(|circuit_vk| circuit_vk.as_slice() == vk)
+                    .map(|j| (i, j))
+            })
+            .ok_or_else(|| AdmissionError::NoProver {
+                vk: hex::encode(vk),
+                fork: fork.as_str().to_string(),
+            })?;
+
+        self.evict(Some(i));
+
+        let release = &self.releases[i];
+        let mut state = release.state();
+        state.last_used = Instant::now();
+        if state.provers.is_none() {
+            state.provers = Some(self.load(release)?);
+        }
+        let provers = state.provers.as_ref().expect("release loaded above");
+        Ok(provers[j].clone())
+    }
+
+    /// Prove `task` with the prover it is routed to, see [`ProverRegistry::route`] and
+    /// [`Prover::gen_proof_universal_with_control`].
+    pub fn gen_proof_universal_with_control(
+        &self,
+        task: &impl ProvingTask,
+        with_snark: bool,
+        control: &ProvingControl,
+    ) -> Result<ProofEnum, Error> {
+        self.route(task)?
+            .gen_proof_universal_with_control(task, with_snark, control)
+    }
+
+    /// Unload the releases no task was routed to for [`ProverRegistry::idle_timeout`],
+    /// returning their names.
+    pub fn evict_idle(&self) -> Vec<String> {
+        self.evict(None)
+    }
+
+    /// Unload the idle releases, except the release at index `keep`.
+    fn evict(&self, keep: Option<usize>) -> Vec<String> {
+        let Some(idle_timeout) = self.idle_timeout else {
+            return Vec::new();
+        };
+        let mut evicted = Vec::new();
+        for (i, release) in self.releases.iter().enumerate() {
+            if Some(i) == keep {
+                continue;
+            }
+            let mut state = release.state();
+            if state.provers.is_some() && state.last_used.elapsed() >= idle_timeout {
+                tracing::info!("unloading idle release {}", release.config.name);
+                state.provers = None;
+                evicted.push(release.config.name.clone());
+            }
+        }
+        evicted
+    }
+
+    /// Load the circuits of `release`. If its vks were read from [`FD_RELEASE_VKS`], they are
+    /// checked against the vks computed from the `app.vmexe` and `openvm.toml` of the circuits,
+    /// see [`compute_app_vk`].
+    fn load(&self, release: &Release) -> Result<Vec<Arc<Prover>>, Error> {
+        let config = &release.config;
+        tracing::info!("loading release {}", config.name);
+        if release.vks_recorded {
+            for (circuit, vk) in config.circuits.iter().zip(&release.vks) {
+                let computed = compute_app_vk(
+                    circuit.dir.join(FD_APP_EXE),
+                    circuit.dir.join(FD_APP_CONFIG),
+                )?;
+                if &computed != vk {
+                    return Err(Error::Setup {
+                        path: release_dir(config).unwrap_or_default().join(FD_RELEASE_VKS),
+                        src: format!(
+                            "vk {} of circuit {} does not match the vk {} computed from its \
+                             assets",
+                            hex::encode(vk),
+                            circuit.name,
+                            hex::encode(&computed)
+                        ),
+                    });
+                }
+            }
+        }
+
+        let dir_cache = self.dir_cache.as_ref().map(|dir| dir.join(&config.name));
+        let circuits = load_circuits(
+            &config.circuits,
+            Some(&config.name),
+            &config.forks,
+            dir_cache.as_deref(),
+            self.dir_keys.as_deref(),
+            self.dir_crash.as_deref(),
+        )?;
+        Ok(circuits
+            .into_iter()
+            .map(|circuit| Arc::new(circuit.prover))
+            .collect())
+    }
+}
+
+/// The release directory, i.e. the parent of the circuit directories.
+fn release_dir(release: &ReleaseConfig) -> Option<&Path> {
+    release
+        .circuits
+        .first()
+        .and_then(|circuit| circuit.dir.parent())
+}
+
+/// Read the app vks of the circuits of `release` from [`FD_RELEASE_VKS`], `None` if the file
+/// or one of the vks is missing.
+fn read_circuit_vks(release: &ReleaseConfig) -> Result<Option<Vec<Vec<u8>>>, Error> {
+    let Some(path) = release_dir(release).map(|dir| dir.join(FD_RELEASE_VKS)) else {
+        return Ok(None);
+    };
+    if !path.exists() {
+        return Ok(None);
+    }
+    let vks = read_release_vks(&path)?;
+    Ok(release
+        .circuits
+        .iter()
+        .map(|circuit| vks.get(&circuit.name).cloned())
+        .collect())
+}
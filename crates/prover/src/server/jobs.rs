@@ -0,0 +1,365 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Condvar, Mutex},
+};
+
+use scroll_zkvm_types::{proof::ProofEnum, task::ProvingTask};
+use serde::Serialize;
+
+use crate::{
+    Error,
+    progress::{CancellationToken, ProvingPhase},
+};
+
+/// Identifier of a job, assigned on submission.
+pub type JobId = u64;
+
+/// The state of a job.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum JobStatus {
+    /// Waiting in the queue.
+    Queued,
+    /// Being proven, in the given phase if reported.
+    Running {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        phase: Option<String>,
+    },
+    /// The proof is available.
+    Done,
+    /// Proving failed.
+    Failed {
+        error: String,
+        /// Whether submitting the task again may succeed, see [`Error::is_retryable`].
+        retryable: bool,
+    },
+    /// Cancelled before the proof was finished.
+    Cancelled,
+}
+
+impl JobStatus {
+    /// Whether the job will not change any more.
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Done | Self::Failed { .. } | Self::Cancelled)
+    }
+}
+
+/// A proving task submitted to the daemon.
+pub struct Job {
+    /// The task to prove.
+    pub task: ProvingTask,
+    /// Whether to wrap the proof into an EVM proof.
+    pub with_snark: bool,
+    /// Name of the circuit the task is routed to.
+    pub circuit: String,
+    /// The current state of the job.
+    pub status: JobStatus,
+    /// The proof, once done.
+    pub proof: Option<Arc<ProofEnum>>,
+    /// Token to cancel the job.
+    pub cancel: CancellationToken,
+}
+
+#[derive(Default)]
+struct State {
+    next_id: JobId,
+    jobs: HashMap<JobId, Job>,
+    pending: VecDeque<JobId>,
+    finished: VecDeque<JobId>,
+}
+
+/// Queue of jobs shared between the HTTP handlers and the proving worker.
+pub struct JobQueue {
+    state: Mutex<State>,
+    cond: Condvar,
+    /// Number of finished jobs (and their proofs) kept around for retrieval.
+    max_finished: usize,
+}
+
+impl JobQueue {
+    /// Create an empty queue keeping up to `max_finished` finished jobs.
+    pub fn new(max_finished: usize) -> Self {
+        Self {
+            state: Default::default(),
+            cond: Condvar::new(),
+            max_finished,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        // A panic while holding the lock does not leave the state inconsistent, all updates are
+        // single assignments.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Enqueue a new job, returning its id.
+    pub fn submit(&self, task: ProvingTask, with_snark: bool, circuit: String) -> JobId {
+        let mut state = self.lock();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.jobs.insert(
+            id,
+            Job {
+                task,
+                with_snark,
+                circuit,
+                status: JobStatus::Queued,
+                proof: None,
+                cancel: CancellationToken::new(),
+            },
+        );
+        state.pending.push_back(id);
+        self.cond.notify_one();
+        id
+    }
+
+    /// Status of the job `id`, if known.
+    pub fn status(&self, id: JobId) -> Option<JobStatus> {
+        self.lock().jobs.get(&id).map(|job| job.status.clone())
+    }
+
+    /// The proof of the job `id`, along with its status.
+    pub fn proof(&self, id: JobId) -> Option<(JobStatus, Option<Arc<ProofEnum>>)> {
+        self.lock()
+            .jobs
+            .get(&id)
+            .map(|job| (job.status.clone(), job.proof.clone()))
+    }
+
+    /// Cancel the job `id`. Returns `false` if the job is unknown.
+    pub fn cancel(&self, id: JobId) -> bool {
+        let mut state = self.lock();
+        let Some(job) = state.jobs.get_mut(&id) else {
+            return false;
+        };
+        job.cancel.cancel();
+        if matches!(job.status, JobStatus::Queued) {
+            job.status = JobStatus::Cancelled;
+            state.pending.retain(|pending| *pending != id);
+            Self::retire(&mut state, id, self.max_finished);
+        }
+        true
+    }
+
+    /// Block until a job is pending, and mark it as running.
+    ///
+    /// Returns the id, task, snark flag, circuit and cancellation token of the job.
+    pub fn next(&self) -> (JobId, ProvingTask, bool, String, CancellationToken) {
+        let mut state = self.lock();
+        loop {
+            if let Some(id) = state.pending.pop_front() {
+                if let Some(job) = state.jobs.get_mut(&id) {
+                    job.status = JobStatus::Running { phase: None };
+                    return (
+                        id,
+                        job.task.clone(),
+                        job.with_snark,
+                        job.circuit.clone(),
+                        job.cancel.clone(),
+                    );
+                }
+            }
+            state = self.cond.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    /// Record the phase a running job entered.
+    pub fn set_phase(&self, id: JobId, phase: &ProvingPhase) {
+        if let Some(job) = self.lock().jobs.get_mut(&id) {
+            if let JobStatus::Running { .. } = job.status {
+                job.status = JobStatus::Running {
+                    phase: Some(phase.to_string()),
+                };
+            }
+        }
+    }
+
+    /// Record the outcome of a job.
+    pub fn finish(&self, id: JobId, result: Result<ProofEnum, Error>) {
+        let mut state = self.lock();
+        let Some(job) = state.jobs.get_mut(&id) else {
+            return;
+        };
+        match result {
+            Ok(proof) => {
+                job.status = JobStatus::Done;
+                job.proof = Some(Arc::new(proof));
+            }
+            Err(Error::Cancelled) => job.status = JobStatus::Cancelled,
+            Err(e) => {
+                job.status = JobStatus::Failed {
+                    error: e.to_string(),
+                    retryable: e.is_retryable(),
+                }
+            }
+        }
+        Self::retire(&mut state, id, self.max_finished);
+    }
+
+    /// Track `id` as finished, dropping the oldest finished jobs beyond `max_finished`.
+    fn retire(state: &mut State, id: JobId, max_finished: usize) {
+        state.finished.push_back(id);
+        while state.finished.len() > max_finished {
+            if let Some(oldest) = state.finished.pop_front() {
+                state.jobs.remove(&oldest);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use scroll_zkvm_types::proof::EvmProof;
+
+    use super::*;
+
+    fn task(identifier: &str) -> ProvingTask {
+        ProvingTask {
+            serialized_witness: vec![],
+            aggregated_proofs: vec![],
+            fork_name: "galileov2".to_string(),
+            vk: vec![],
+            identifier: identifier.to_string(),
+            input_commits: vec![],
+        }
+    }
+
+    fn proof() -> ProofEnum {
+        ProofEnum::Evm(EvmProof {
+            proof: vec![1],
+            instances: vec![2],
+            stat: None,
+        })
+    }
+
+    #[test]
+    fn jobs_run_in_submission_order() {
+        let queue = JobQueue::new(4);
+        let first = queue.submit(task("first"), false, "chunk".to_string());
+        let second = queue.submit(task("second"), true, "batch".to_string());
+        assert_ne!(first, second);
+        assert!(matches!(queue.status(first), Some(JobStatus::Queued)));
+
+        let (id, task, with_snark, circuit, cancel) = queue.next();
+        assert_eq!(id, first);
+        assert_eq!(task.identifier, "first");
+        assert!(!with_snark);
+        assert_eq!(circuit, "chunk");
+        assert!(!cancel.is_cancelled());
+        assert!(matches!(
+            queue.status(first),
+            Some(JobStatus::Running { phase: None })
+        ));
+        assert!(matches!(queue.status(second), Some(JobStatus::Queued)));
+
+        let (id, _, with_snark, circuit, _) = queue.next();
+        assert_eq!(id, second);
+        assert!(with_snark);
+        assert_eq!(circuit, "batch");
+    }
+
+    #[test]
+    fn set_phase_of_running_job() {
+        let queue = JobQueue::new(4);
+        let id = queue.submit(task("task"), false, "chunk".to_string());
+        queue.set_phase(id, &ProvingPhase::Leaf);
+        assert!(matches!(queue.status(id), Some(JobStatus::Queued)));
+
+        queue.next();
+        queue.set_phase(id, &ProvingPhase::AppSegment { index: 0, total: 2 });
+        match queue.status(id) {
+            Some(JobStatus::Running { phase }) => {
+                assert_eq!(phase.as_deref(), Some("app segment 1/2"))
+            }
+            status => panic!("unexpected status {status:?}"),
+        }
+
+        queue.finish(id, Ok(proof()));
+        queue.set_phase(id, &ProvingPhase::Halo2);
+        assert!(matches!(queue.status(id), Some(JobStatus::Done)));
+    }
+
+    #[test]
+    fn finish_records_outcome() {
+        let queue = JobQueue::new(4);
+        let done = queue.submit(task("done"), false, "chunk".to_string());
+        let retryable = queue.submit(task("retryable"), false, "chunk".to_string());
+        let fatal = queue.submit(task("fatal"), false, "chunk".to_string());
+
+        queue.finish(done, Ok(proof()));
+        let (status, proof) = queue.proof(done).unwrap();
+        assert!(matches!(status, JobStatus::Done));
+        assert!(proof.is_some());
+
+        queue.finish(retryable, Err(Error::ResourceExhausted("oom".to_string())));
+        assert!(matches!(
+            queue.status(retryable),
+            Some(JobStatus::Failed {
+                retryable: true,
+                ..
+            })
+        ));
+
+        queue.finish(fatal, Err(Error::Custom("bad".to_string())));
+        match queue.proof(fatal) {
+            Some((JobStatus::Failed { error, retryable }, None)) => {
+                assert!(error.contains("bad"));
+                assert!(!retryable);
+            }
+            _ => panic!("unexpected outcome"),
+        }
+    }
+
+    #[test]
+    fn cancel_queued_job() {
+        let queue = JobQueue::new(4);
+        let cancelled = queue.submit(task("cancelled"), false, "chunk".to_string());
+        let kept = queue.submit(task("kept"), false, "chunk".to_string());
+
+        assert!(queue.cancel(cancelled));
+        assert!(matches!(
+            queue.status(cancelled),
+            Some(JobStatus::Cancelled)
+        ));
+        assert!(!queue.cancel(kept + 1));
+
+        // The cancelled job is not handed out any more.
+        assert_eq!(queue.next().0, kept);
+    }
+
+    #[test]
+    fn cancel_running_job() {
+        let queue = JobQueue::new(4);
+        let id = queue.submit(task("task"), false, "chunk".to_string());
+        let (_, _, _, _, cancel) = queue.next();
+
+        // The worker notices the cancellation and finishes the job.
+        assert!(queue.cancel(id));
+        assert!(cancel.is_cancelled());
+        assert!(matches!(queue.status(id), Some(JobStatus::Running { .. })));
+        queue.finish(id, Err(Error::Cancelled));
+        assert!(matches!(queue.status(id), Some(JobStatus::Cancelled)));
+    }
+
+    #[test]
+    fn finished_jobs_are_dropped_beyond_max() {
+        let queue = JobQueue::new(2);
+        let ids: Vec<_> = (0..3)
+            .map(|i| queue.submit(task(&i.to_string()), false, "chunk".to_string()))
+            .collect();
+        for &id in &ids {
+            queue.finish(id, Ok(proof()));
+        }
+        assert!(queue.status(ids[0]).is_none());
+        assert!(
+            queue
+                .status(ids[1])
+                .is_some_and(|status| status.is_finished())
+        );
+        assert!(
+            queue
+                .status(ids[2])
+                .is_some_and(|status| status.is_finished())
+        );
+    }
+}
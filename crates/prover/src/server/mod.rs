@@ -0,0 +1,459 @@
+//! Long-running prover daemon exposing a local HTTP job API.
+//!
+//! Endpoints:
+//!
+//! - `POST /v1/jobs[?snark=true]`: submit a [`ProvingTask`] (JSON body), returns `{"id": ..}`.
+//! - `GET /v1/jobs/{id}`: status of the job.
+//! - `GET /v1/jobs/{id}/proof`: the resulting [`ProofEnum`][scroll_zkvm_types::proof::ProofEnum].
+//! - `DELETE /v1/jobs/{id}`: cancel the job.
+//! - `GET /v1/capabilities`: loaded circuits, their VKs and the supported forks.
+//!
+//! Jobs are proven one at a time, in submission order, by a single worker thread owning the
+//! provers.
+
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use scroll_zkvm_types::task::ProvingTask;
+use serde::{Deserialize, Serialize};
+use tiny_http::{Header, Method, Request, Response};
+
+use crate::{
+    Error,
+    circuits::{Circuit, CircuitConfig, load_circuits},
+    progress::ProvingControl,
+    utils::read,
+};
+
+mod jobs;
+pub use jobs::{JobId, JobQueue, JobStatus};
+
+fn default_listen() -> String {
+    "127.0.0.1:8555".to_string()
+}
+
+fn default_max_finished_jobs() -> usize {
+    64
+}
+
+/// Configuration of the daemon, read from a TOML file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ServerConfig {
+    /// Address to listen on.
+    #[serde(default = "default_listen")]
+    pub listen: String,
+    /// Name of the guest release of the circuits, reported in the stats of their proofs.
+    #[serde(default)]
+    pub release: Option<String>,
+    /// Forks the loaded circuits can prove.
+    pub forks: Vec<String>,
+    /// Number of finished jobs kept for retrieval of their status and proof.
+    #[serde(default = "default_max_finished_jobs")]
+    pub max_finished_jobs: usize,
+    /// Optional directory to persist generated proofs in.
+    #[serde(default)]
+    pub dir_cache: Option<PathBuf>,
+    /// Optional directory to cache the proving keys in across restarts.
+    #[serde(default)]
+    pub dir_keys: Option<PathBuf>,
+    /// Optional directory to write crash bundles of failed jobs in.
+    #[serde(default)]
+    pub dir_crash: Option<PathBuf>,
+    /// The circuits to load.
+    pub circuits: Vec<CircuitConfig>,
+}
+
+impl ServerConfig {
+    /// Read the configuration from the TOML file at `path`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let bytes = read(path)?;
+        let toml_str = String::from_utf8_lossy(&bytes);
+        toml::from_str(&toml_str).map_err(|e| Error::Setup {
+            path: path.into(),
+            src: e.to_string(),
+        })
+    }
+}
+
+/// A loaded circuit, as listed by the capabilities endpoint.
+#[derive(Clone, Debug, Serialize)]
+pub struct CircuitCapability {
+    /// Name of the circuit.
+    pub name: String,
+    /// Hex-encoded app vk.
+    pub vk: String,
+}
+
+/// Response of the capabilities endpoint.
+#[derive(Clone, Debug, Serialize)]
+pub struct Capabilities {
+    /// The loaded circuits.
+    pub circuits: Vec<CircuitCapability>,
+    /// Forks the loaded circuits can prove.
+    pub forks: Vec<String>,
+    /// Git version of the prover.
+    pub version: String,
+}
+
+/// Route `task` to the circuit with a matching vk.
+fn route(
+    capabilities: &Capabilities,
+    forks: &[String],
+    task: &ProvingTask,
+) -> Result<String, String> {
+    if !forks
+        .iter()
+        .any(|fork| fork.eq_ignore_ascii_case(&task.fork_name))
+    {
+        return Err(format!("unsupported fork {}", task.fork_name));
+    }
+    let vk = hex::encode(&task.vk);
+    capabilities
+        .circuits
+        .iter()
+        .find(|circuit| circuit.vk == vk)
+        .map(|circuit| circuit.name.clone())
+        .ok_or_else(|| format!("no circuit loaded for vk {vk}"))
+}
+
+/// Run the daemon until the process is terminated.
+pub fn run(config: ServerConfig) -> Result<(), Error> {
+    let circuits = load_circuits(
+        &config.circuits,
+        config.release.as_deref(),
+        &config.forks,
+        config.dir_cache.as_deref(),
+        config.dir_keys.as_deref(),
+        config.dir_crash.as_deref(),
+    )?;
+    let capabilities = Capabilities {
+        circuits: circuits
+            .iter()
+            .map(|circuit| CircuitCapability {
+                name: circuit.name.clone(),
+                vk: hex::encode(&circuit.vk),
+            })
+            .collect(),
+        forks: config.forks.clone(),
+        version: crate::utils::short_git_version(),
+    };
+
+    let queue = Arc::new(JobQueue::new(config.max_finished_jobs));
+    {
+        let queue = queue.clone();
+        std::thread::Builder::new()
+            .name("prover-worker".to_string())
+            .spawn(move || work(circuits, queue))?;
+    }
+
+    let server = tiny_http::Server::http(&config.listen)
+        .map_err(|e| Error::Custom(format!("failed to listen on {}: {e}", config.listen)))?;
+    tracing::info!("listening on {}", config.listen);
+
+    for mut request in server.incoming_requests() {
+        let method = request.method().clone();
+        let url = request.url().to_string();
+        let reply = handle(
+            &method,
+            &url,
+            request.as_reader(),
+            &capabilities,
+            &config.forks,
+            &queue,
+        );
+        respond(request, reply);
+    }
+    Ok(())
+}
+
+/// Prove the queued jobs one after another.
+fn work(mut circuits: Vec<Circuit>, queue: Arc<JobQueue>) {
+    loop {
+        let (id, task, with_snark, circuit, cancel) = queue.next();
+        let Some(circuit) = circuits.iter_mut().find(|c| c.name == circuit) else {
+            queue.finish(id, Err(Error::Custom(format!("unknown circuit {circuit}"))));
+            continue;
+        };
+        tracing::info!(
+            "proving job {id} ({}) with {}",
+            task.identifier,
+            circuit.name
+        );
+        let control = {
+            let queue = queue.clone();
+            ProvingControl::new(cancel).with_progress(move |phase| queue.set_phase(id, phase))
+        };
+        let result = if task.aggregated_proofs.is_empty() {
+            circuit
+                .prover
+                .gen_proof_universal_with_control(&task, with_snark, &control)
+        } else {
+            circuit.prover.prove_aggregation_with_control(
+                &task,
+                &task.aggregated_proofs,
+                with_snark,
+                &control,
+            )
+        };
+        if let Err(e) = &result {
+            tracing::warn!("job {id} failed: {e}");
+        }
+        queue.finish(id, result);
+    }
+}
+
+/// A response to send: status code and JSON body.
+struct Reply {
+    status: u16,
+    body: String,
+}
+
+impl Reply {
+    fn json<T: Serialize>(status: u16, value: &T) -> Self {
+        match serde_json::to_string(value) {
+            Ok(body) => Self { status, body },
+            Err(e) => Self::error(500, e.to_string()),
+        }
+    }
+
+    fn error(status: u16, error: impl Into<String>) -> Self {
+        #[derive(Serialize)]
+        struct ErrorBody {
+            error: String,
+        }
+        Self::json(
+            status,
+            &ErrorBody {
+                error: error.into(),
+            },
+        )
+    }
+}
+
+fn respond(request: Request, reply: Reply) {
+    let header = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+        .expect("static header is valid");
+    let response = Response::from_string(reply.body)
+        .with_status_code(reply.status)
+        .with_header(header);
+    if let Err(e) = request.respond(response) {
+        tracing::warn!("failed to send response: {e}");
+    }
+}
+
+fn handle(
+    method: &Method,
+    url: &str,
+    body: &mut dyn Read,
+    capabilities: &Capabilities,
+    forks: &[String],
+    queue: &JobQueue,
+) -> Reply {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+    match (method, segments.as_slice()) {
+        (Method::Get, ["v1", "capabilities"]) => Reply::json(200, capabilities),
+        (Method::Post, ["v1", "jobs"]) => {
+            let with_snark = query
+                .split('&')
+                .any(|param| param == "snark=true" || param == "snark=1");
+            let task: ProvingTask = match serde_json::from_reader(body) {
+                Ok(task) => task,
+                Err(e) => return Reply::error(400, format!("invalid task: {e}")),
+            };
+            let circuit = match route(capabilities, forks, &task) {
+                Ok(circuit) => circuit,
+                Err(e) => return Reply::error(400, e),
+            };
+            #[derive(Serialize)]
+            struct Submitted {
+                id: JobId,
+            }
+            let id = queue.submit(task, with_snark, circuit);
+            Reply::json(202, &Submitted { id })
+        }
+        (Method::Get, ["v1", "jobs", id]) => {
+            match id.parse().ok().and_then(|id| queue.status(id)) {
+                Some(status) => Reply::json(200, &status),
+                None => Reply::error(404, "unknown job"),
+            }
+        }
+        (Method::Get, ["v1", "jobs", id, "proof"]) => {
+            match id.parse().ok().and_then(|id| queue.proof(id)) {
+                Some((_, Some(proof))) => Reply::json(200, proof.as_ref()),
+                Some((status, None)) => Reply::json(409, &status),
+                None => Reply::error(404, "unknown job"),
+            }
+        }
+        (Method::Delete, ["v1", "jobs", id]) => {
+            match id.parse().ok().filter(|id| queue.cancel(*id)) {
+                Some(id) => Reply::json(200, &queue.status(id)),
+                None => Reply::error(404, "unknown job"),
+            }
+        }
+        _ => Reply::error(404, "not found"),
+    }
+}
+
+/// Install the default tracing subscriber of the daemon.
+pub fn init_tracing() {
+    use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    if let Err(e) = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .try_init()
+    {
+        eprintln!("failed to install tracing subscriber: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use scroll_zkvm_types::proof::{EvmProof, ProofEnum};
+    use serde_json::Value;
+
+    use super::*;
+
+    const VK: [u8; 3] = [1, 2, 3];
+
+    fn capabilities() -> Capabilities {
+        Capabilities {
+            circuits: vec![CircuitCapability {
+                name: "chunk".to_string(),
+                vk: hex::encode(VK),
+            }],
+            forks: forks(),
+            version: "test".to_string(),
+        }
+    }
+
+    fn forks() -> Vec<String> {
+        vec!["galileov2".to_string()]
+    }
+
+    fn task(fork_name: &str, vk: &[u8]) -> Vec<u8> {
+        serde_json::to_vec(&ProvingTask {
+            serialized_witness: vec![vec![0]],
+            aggregated_proofs: vec![],
+            fork_name: fork_name.to_string(),
+            vk: vk.to_vec(),
+            identifier: "task".to_string(),
+            input_commits: vec![],
+        })
+        .unwrap()
+    }
+
+    fn request(queue: &JobQueue, method: Method, url: &str, body: &[u8]) -> (u16, Value) {
+        let reply = handle(
+            &method,
+            url,
+            &mut &body[..],
+            &capabilities(),
+            &forks(),
+            queue,
+        );
+        (reply.status, serde_json::from_str(&reply.body).unwrap())
+    }
+
+    #[test]
+    fn capabilities_endpoint() {
+        let queue = JobQueue::new(4);
+        let (status, body) = request(&queue, Method::Get, "/v1/capabilities", b"");
+        assert_eq!(status, 200);
+        assert_eq!(body["circuits"][0]["name"], "chunk");
+        assert_eq!(body["circuits"][0]["vk"], hex::encode(VK));
+        assert_eq!(body["forks"][0], "galileov2");
+    }
+
+    #[test]
+    fn submit_routes_by_fork_and_vk() {
+        let queue = JobQueue::new(4);
+        let (status, body) = request(&queue, Method::Post, "/v1/jobs", &task("GalileoV2", &VK));
+        assert_eq!(status, 202);
+        assert_eq!(body["id"], 0);
+        assert!(matches!(queue.status(0), Some(JobStatus::Queued)));
+
+        let (status, body) = request(&queue, Method::Post, "/v1/jobs", &task("feynman", &VK));
+        assert_eq!(status, 400);
+        assert_eq!(body["error"], "unsupported fork feynman");
+
+        let (status, _) = request(&queue, Method::Post, "/v1/jobs", &task("galileov2", &[9]));
+        assert_eq!(status, 400);
+
+        let (status, _) = request(&queue, Method::Post, "/v1/jobs", b"not json");
+        assert_eq!(status, 400);
+        assert!(queue.status(1).is_none());
+    }
+
+    #[test]
+    fn submit_with_snark() {
+        let queue = JobQueue::new(4);
+        let url = "/v1/jobs?snark=true";
+        let (status, _) = request(&queue, Method::Post, url, &task("galileov2", &VK));
+        assert_eq!(status, 202);
+        let (_, _, with_snark, circuit, _) = queue.next();
+        assert!(with_snark);
+        assert_eq!(circuit, "chunk");
+    }
+
+    #[test]
+    fn job_status_and_proof() {
+        let queue = JobQueue::new(4);
+        request(&queue, Method::Post, "/v1/jobs", &task("galileov2", &VK));
+
+        let (status, body) = request(&queue, Method::Get, "/v1/jobs/0", b"");
+        assert_eq!(status, 200);
+        assert_eq!(body["status"], "queued");
+
+        let (status, body) = request(&queue, Method::Get, "/v1/jobs/0/proof", b"");
+        assert_eq!(status, 409);
+        assert_eq!(body["status"], "queued");
+
+        queue.next();
+        let proof = EvmProof {
+            proof: vec![1],
+            instances: vec![2],
+            stat: None,
+        };
+        queue.finish(0, Ok(ProofEnum::Evm(proof)));
+        let (status, body) = request(&queue, Method::Get, "/v1/jobs/0", b"");
+        assert_eq!(status, 200);
+        assert_eq!(body["status"], "done");
+        let (status, _) = request(&queue, Method::Get, "/v1/jobs/0/proof", b"");
+        assert_eq!(status, 200);
+
+        for url in ["/v1/jobs/1", "/v1/jobs/1/proof", "/v1/jobs/x"] {
+            let (status, _) = request(&queue, Method::Get, url, b"");
+            assert_eq!(status, 404, "{url}");
+        }
+    }
+
+    #[test]
+    fn cancel_job() {
+        let queue = JobQueue::new(4);
+        request(&queue, Method::Post, "/v1/jobs", &task("galileov2", &VK));
+
+        let (status, body) = request(&queue, Method::Delete, "/v1/jobs/0", b"");
+        assert_eq!(status, 200);
+        assert_eq!(body["status"], "cancelled");
+
+        let (status, _) = request(&queue, Method::Delete, "/v1/jobs/1", b"");
+        assert_eq!(status, 404);
+    }
+
+    #[test]
+    fn unknown_route() {
+        let queue = JobQueue::new(4);
+        let (status, _) = request(&queue, Method::Get, "/v1/other", b"");
+        assert_eq!(status, 404);
+        let (status, _) = request(&queue, Method::Put, "/v1/jobs", b"");
+        assert_eq!(status, 404);
+    }
+}
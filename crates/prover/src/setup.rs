@@ -1,17 +1,33 @@
-use std::{collections::BTreeMap, fs::read_to_string, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::read_to_string,
+    path::Path,
+};
+
+use base64::{Engine, prelude::BASE64_STANDARD};
 
 use openvm_circuit::arch::instructions::{
     exe::{FnBounds, VmExe},
     instruction::{DebugInfo, Instruction},
     program::Program,
 };
-use openvm_sdk::F;
+use openvm_continuations::CommitBytes;
+use openvm_recursion_circuit::batch_constraint::commit_child_vk;
 use openvm_sdk::config::AppConfig;
 use openvm_sdk::fs::read_object_from_file;
+use openvm_sdk::keygen::AppProvingKey;
+use openvm_sdk::{F, Sdk};
 use openvm_sdk_config::SdkVmConfig;
+use openvm_stark_backend::StarkEngine;
+use openvm_stark_sdk::config::baby_bear_poseidon2::BabyBearPoseidon2CpuEngine;
+use openvm_stark_sdk::openvm_stark_backend::p3_field::PrimeField32;
+use scroll_zkvm_types::{types_agg::ProgramCommitment, utils::serialize_vk};
 
 use crate::Error;
 
+/// Path of the app vks of a release, relative to the release directory, written by build-guest.
+pub const FD_RELEASE_VKS: &str = "verifier/openVmVk.json";
+
 /// Wrapper around [`openvm_sdk::fs::read_exe_from_file`].
 pub fn read_app_exe<P: AsRef<Path>>(path: P) -> Result<VmExe<F>, Error> {
     if let Ok(r) = read_object_from_file(&path) {
@@ -64,7 +80,7 @@ pub fn read_app_exe<P: AsRef<Path>>(path: P) -> Result<VmExe<F>, Error> {
         path: path.as_ref().into(),
         src: e.to_string(),
     })?;
-    use openvm_stark_sdk::openvm_stark_backend::p3_field::{PrimeField32, integers::QuotientMap};
+    use openvm_stark_sdk::openvm_stark_backend::p3_field::integers::QuotientMap;
     let exe = VmExe::<F> {
         program: Program::<F> {
             instructions_and_debug_infos: old_exe.program.instructions_and_debug_infos,
@@ -96,3 +112,84 @@ pub fn read_app_config<P: AsRef<Path>>(path: P) -> Result<AppConfig<SdkVmConfig>
         src: e.to_string(),
     })
 }
+
+/// Compute the [`ProgramCommitment`] of `app_exe` run with `app_config`.
+///
+/// Only the app keys are generated, not the aggregation ones, so that the commitment can be
+/// computed or checked without setting up the whole SDK. This is how [`Prover::setup`] computes
+/// the commitment of its app, see [`Prover::get_app_commitment`].
+///
+/// [`Prover::setup`]: crate::Prover::setup
+/// [`Prover::get_app_commitment`]: crate::Prover::get_app_commitment
+pub fn compute_program_commitment(
+    app_exe: VmExe<F>,
+    app_config: AppConfig<SdkVmConfig>,
+) -> Result<ProgramCommitment, Error> {
+    compute_program_commitment_with_app_pk(app_exe, app_config).map(|(commitment, _)| commitment)
+}
+
+/// Same as [`compute_program_commitment`], also returning the app proving key generated to
+/// compute the commitment, so that the SDK proving the app can be built with it instead of
+/// generating it again.
+pub(crate) fn compute_program_commitment_with_app_pk(
+    app_exe: VmExe<F>,
+    app_config: AppConfig<SdkVmConfig>,
+) -> Result<(ProgramCommitment, AppProvingKey<SdkVmConfig>), Error> {
+    let sdk = Sdk::builder()
+        .app_config(app_config)
+        .build()
+        .map_err(|e| Error::Commit(e.to_string()))?;
+    let exe_digest = sdk
+        .app_exe_commit(&app_exe)
+        .map_err(|e| Error::Commit(e.to_string()))?;
+    let commitment = ProgramCommitment {
+        exe: std::array::from_fn(|i| exe_digest[i].as_canonical_u32()),
+        vm: app_vm_commit(&sdk),
+    };
+    Ok((commitment, sdk.app_pk().clone()))
+}
+
+/// The vm commit of the app of `sdk`, i.e. the commit of its app vk as exposed by the proofs of
+/// the app.
+///
+/// Unlike the exe commit, it only depends on the app config.
+pub fn app_vm_commit(sdk: &Sdk) -> [u32; 8] {
+    let app_vk = sdk.app_pk().app_vm_pk.vm_pk.get_vk();
+    let engine = <BabyBearPoseidon2CpuEngine as StarkEngine>::new(app_vk.inner.params.clone());
+    let commit: CommitBytes = commit_child_vk(&engine, &app_vk, false).commitment.into();
+    let digest: [F; 8] = commit.into();
+    digest.map(|f| f.as_canonical_u32())
+}
+
+/// Compute the serialized app vk of the app at `path_app_exe` with the config at
+/// `path_app_config`, see [`compute_program_commitment`] and
+/// [`Prover::get_app_vk`][crate::Prover::get_app_vk].
+pub fn compute_app_vk<P: AsRef<Path>, Q: AsRef<Path>>(
+    path_app_exe: P,
+    path_app_config: Q,
+) -> Result<Vec<u8>, Error> {
+    let app_exe = read_app_exe(path_app_exe)?;
+    let app_config = read_app_config(path_app_config)?;
+    let commitment = compute_program_commitment(app_exe, app_config)?;
+    Ok(serialize_vk::serialize(&commitment))
+}
+
+/// Read the app vks of a release from the JSON file at `path`, see [`FD_RELEASE_VKS`], keyed by
+/// circuit name.
+pub fn read_release_vks<P: AsRef<Path>>(path: P) -> Result<HashMap<String, Vec<u8>>, Error> {
+    let path = path.as_ref();
+    let vks: HashMap<String, String> = crate::utils::read_json(path)?;
+    vks.into_iter()
+        .map(|(key, vk)| {
+            let name = key.strip_suffix("_vk").unwrap_or(&key).to_string();
+            // Older releases encoded the vks in base64.
+            let vk = hex::decode(&vk)
+                .or_else(|_| BASE64_STANDARD.decode(&vk))
+                .map_err(|e| Error::Setup {
+                    path: path.into(),
+                    src: format!("vk of circuit {name}: {e}"),
+                })?;
+            Ok((name, vk))
+        })
+        .collect()
+}
@@ -0,0 +1,300 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use scroll_zkvm_types::{proof::ProofEnum, utils::keccak256};
+
+use crate::{
+    Error,
+    utils::{read_json, write_json},
+};
+
+/// The kind of proof held in a [`ProofStore`].
+///
+/// A STARK and an EVM proof for the same task are different artifacts, so the kind is part of
+/// the key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProofKind {
+    /// The root STARK proof, see [`Prover::gen_proof_stark`][crate::Prover::gen_proof_stark].
+    Stark,
+    /// The halo2 wrapped proof, see [`Prover::gen_proof_snark`][crate::Prover::gen_proof_snark].
+    Evm,
+}
+
+impl ProofKind {
+    /// Short name used as the prefix of on-disk entries.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Stark => "stark",
+            Self::Evm => "evm",
+        }
+    }
+}
+
+/// Content-addressed key of a proof in a [`ProofStore`].
+///
+/// Two tasks map to the same key only if they carry the same identifier, are proven by the same
+/// app (vk) under the same fork and feed the same witness to the guest.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProofKey {
+    /// Identifier of the proving task.
+    pub task_id: String,
+    /// Serialized app vk of the prover, see [`Prover::get_app_vk`][crate::Prover::get_app_vk].
+    pub app_vk: Vec<u8>,
+    /// Fork the task is proven under.
+    pub fork_name: String,
+    /// Hash of the serialized witness, see
+    /// [`ProvingTask::witness_hash`][crate::task::ProvingTask::witness_hash].
+    pub witness_hash: [u8; 32],
+    /// Whether the entry is a STARK or an EVM proof.
+    pub kind: ProofKind,
+}
+
+impl ProofKey {
+    /// Digest over all fields of the key.
+    pub fn digest(&self) -> [u8; 32] {
+        let mut buf = Vec::with_capacity(
+            self.task_id.len() + self.app_vk.len() + self.fork_name.len() + 32 + 32,
+        );
+        for field in [
+            self.task_id.as_bytes(),
+            self.app_vk.as_slice(),
+            self.fork_name.as_bytes(),
+            self.witness_hash.as_slice(),
+            self.kind.as_str().as_bytes(),
+        ] {
+            // Length-prefix every field so that adjacent fields can not be shifted into each
+            // other.
+            buf.extend_from_slice(&(field.len() as u64).to_le_bytes());
+            buf.extend_from_slice(field);
+        }
+        keccak256(buf).0
+    }
+}
+
+/// Storage for proofs that have already been generated.
+///
+/// [`Prover::gen_proof_universal`][crate::Prover::gen_proof_universal] looks up the store before
+/// proving and writes the new proof back once it is generated.
+pub trait ProofStore: Send + Sync {
+    /// Get the proof stored under `key`, if any.
+    fn get(&self, key: &ProofKey) -> Result<Option<ProofEnum>, Error>;
+
+    /// Store `proof` under `key`, replacing any previous entry.
+    fn put(&self, key: &ProofKey, proof: &ProofEnum) -> Result<(), Error>;
+}
+
+/// [`ProofStore`] persisting proofs as JSON files in a directory.
+///
+/// Entries survive a restart of the prover, which is what makes it possible to skip proofs that
+/// were finished before the restart.
+#[derive(Debug, Clone)]
+pub struct FsProofStore {
+    dir: PathBuf,
+}
+
+impl FsProofStore {
+    /// Open a store in `dir`, creating the directory if it does not exist.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Result<Self, Error> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir).map_err(|source| Error::IoReadWrite {
+            path: dir.clone(),
+            source,
+        })?;
+        Ok(Self { dir })
+    }
+
+    /// The directory backing this store.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Path of the entry for `key`.
+    pub fn path(&self, key: &ProofKey) -> PathBuf {
+        self.dir.join(format!(
+            "{}-{}.json",
+            key.kind.as_str(),
+            hex::encode(key.digest())
+        ))
+    }
+}
+
+impl ProofStore for FsProofStore {
+    fn get(&self, key: &ProofKey) -> Result<Option<ProofEnum>, Error> {
+        let path = self.path(key);
+        if !path.exists() {
+            return Ok(None);
+        }
+        read_json(&path).map(Some)
+    }
+
+    fn put(&self, key: &ProofKey, proof: &ProofEnum) -> Result<(), Error> {
+        let path = self.path(key);
+        // Write to a temporary file first, so that a crash halfway does not leave behind a
+        // truncated entry that would be picked up on the next start. The temporary file is
+        // unique, so that concurrent puts of the same key do not write into the same file.
+        static NUM_PUTS: AtomicU64 = AtomicU64::new(0);
+        let path_tmp = path.with_extension(format!(
+            "json.{}-{}.tmp",
+            std::process::id(),
+            NUM_PUTS.fetch_add(1, Ordering::Relaxed)
+        ));
+        write_json(&path_tmp, proof)?;
+        std::fs::rename(&path_tmp, &path).map_err(|source| Error::IoReadWrite { path, source })
+    }
+}
+
+/// [`ProofStore`] keeping proofs in memory, mostly useful for tests and short-lived processes.
+#[derive(Default)]
+pub struct MemProofStore {
+    proofs: Mutex<HashMap<ProofKey, ProofEnum>>,
+}
+
+impl MemProofStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ProofStore for MemProofStore {
+    fn get(&self, key: &ProofKey) -> Result<Option<ProofEnum>, Error> {
+        let proofs = self
+            .proofs
+            .lock()
+            .map_err(|e| Error::Custom(e.to_string()))?;
+        Ok(proofs.get(key).cloned())
+    }
+
+    fn put(&self, key: &ProofKey, proof: &ProofEnum) -> Result<(), Error> {
+        let mut proofs = self
+            .proofs
+            .lock()
+            .map_err(|e| Error::Custom(e.to_string()))?;
+        proofs.insert(key.clone(), proof.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use scroll_zkvm_types::proof::EvmProof;
+
+    use super::*;
+
+    fn key(task_id: &str) -> ProofKey {
+        ProofKey {
+            task_id: task_id.to_string(),
+            app_vk: vec![1, 2, 3],
+            fork_name: "galileov2".to_string(),
+            witness_hash: [7; 32],
+            kind: ProofKind::Evm,
+        }
+    }
+
+    fn proof(byte: u8) -> ProofEnum {
+        ProofEnum::Evm(EvmProof {
+            proof: vec![byte; 8],
+            instances: vec![byte; 4],
+            stat: None,
+        })
+    }
+
+    fn evm_proof(proof: Option<ProofEnum>) -> Option<EvmProof> {
+        proof.and_then(ProofEnum::into_evm_proof)
+    }
+
+    /// A fresh directory for the test `name`.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("scroll-zkvm-store-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn key_digest_covers_all_fields() {
+        let base = key("task");
+        let mut other = base.clone();
+        other.kind = ProofKind::Stark;
+        assert_ne!(base.digest(), other.digest());
+        let mut other = base.clone();
+        other.witness_hash = [8; 32];
+        assert_ne!(base.digest(), other.digest());
+        let mut other = base.clone();
+        other.fork_name = "feynman".to_string();
+        assert_ne!(base.digest(), other.digest());
+        assert_eq!(base.digest(), key("task").digest());
+    }
+
+    #[test]
+    fn mem_store_round_trip() -> Result<(), Error> {
+        let store = MemProofStore::new();
+        assert!(store.get(&key("a"))?.is_none());
+
+        store.put(&key("a"), &proof(1))?;
+        assert_eq!(evm_proof(store.get(&key("a"))?), evm_proof(Some(proof(1))));
+        assert!(store.get(&key("b"))?.is_none());
+
+        store.put(&key("a"), &proof(2))?;
+        assert_eq!(evm_proof(store.get(&key("a"))?), evm_proof(Some(proof(2))));
+        Ok(())
+    }
+
+    #[test]
+    fn fs_store_round_trip() -> Result<(), Error> {
+        let dir = test_dir("round-trip");
+        let store = FsProofStore::new(&dir)?;
+        assert!(store.get(&key("a"))?.is_none());
+
+        store.put(&key("a"), &proof(1))?;
+        assert_eq!(evm_proof(store.get(&key("a"))?), evm_proof(Some(proof(1))));
+        assert!(store.get(&key("b"))?.is_none());
+
+        // Entries survive reopening the store, and no temporary file is left behind.
+        let store = FsProofStore::new(&dir)?;
+        assert_eq!(evm_proof(store.get(&key("a"))?), evm_proof(Some(proof(1))));
+        let entries = std::fs::read_dir(&dir).unwrap().count();
+        assert_eq!(entries, 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn fs_store_corrupt_entry() -> Result<(), Error> {
+        let dir = test_dir("corrupt");
+        let store = FsProofStore::new(&dir)?;
+        std::fs::write(store.path(&key("a")), b"{ not a proof").unwrap();
+        assert!(store.get(&key("a")).is_err());
+
+        // A new put replaces the corrupt entry.
+        store.put(&key("a"), &proof(3))?;
+        assert_eq!(evm_proof(store.get(&key("a"))?), evm_proof(Some(proof(3))));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn fs_store_concurrent_puts() -> Result<(), Error> {
+        let dir = test_dir("concurrent");
+        let store = FsProofStore::new(&dir)?;
+        std::thread::scope(|s| {
+            for byte in 0..8 {
+                let store = &store;
+                s.spawn(move || store.put(&key("a"), &proof(byte)).unwrap());
+            }
+        });
+        let stored = evm_proof(store.get(&key("a"))?).expect("entry written");
+        assert!(stored.proof.iter().all(|&b| b == stored.proof[0]));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        Ok(())
+    }
+}
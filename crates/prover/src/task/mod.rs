@@ -1,11 +1,16 @@
 use openvm_sdk::StdIn;
-use scroll_zkvm_types::{public_inputs::ForkName, task::ProvingTask as UniversalProvingTask};
+use openvm_stark_sdk::openvm_stark_backend::p3_field::PrimeField32;
+use scroll_zkvm_types::{
+    public_inputs::ForkName, task::ProvingTask as UniversalProvingTask, utils::keccak256,
+};
+
+use crate::AdmissionError;
 
 /// Every proving task must have an identifier. The identifier will be appended to a prefix while
 /// storing/reading proof to/from disc.
 /// Every proving task must have an identifier. The identifier will be appended to a prefix while
 /// storing/reading proof to/from disc.
-pub trait ProvingTask: serde::de::DeserializeOwned {
+pub trait ProvingTask: serde::Serialize + serde::de::DeserializeOwned {
     fn identifier(&self) -> String;
 
     fn build_guest_input_inner(&self, stdin: &mut StdIn);
@@ -17,6 +22,37 @@ pub trait ProvingTask: serde::de::DeserializeOwned {
     }
 
     fn fork_name(&self) -> ForkName;
+
+    /// Same as [`ProvingTask::fork_name`], failing instead of panicking on an unknown fork.
+    fn try_fork_name(&self) -> Result<ForkName, AdmissionError> {
+        Ok(self.fork_name())
+    }
+
+    /// The app vk of the circuit expected to prove this task, empty if not specified.
+    fn vk(&self) -> &[u8] {
+        &[]
+    }
+
+    /// The version byte leading the serialized witness, if the task carries one.
+    fn witness_version(&self) -> Result<Option<u8>, AdmissionError> {
+        Ok(None)
+    }
+
+    /// Hash of the guest input, used to key the proof in a
+    /// [`ProofStore`][crate::store::ProofStore].
+    ///
+    /// Defaults to hashing the guest input built by [`ProvingTask::build_guest_input`].
+    fn witness_hash(&self) -> [u8; 32] {
+        let stdin = self.build_guest_input();
+        let mut bytes = Vec::new();
+        for item in &stdin.buffer {
+            bytes.extend_from_slice(&(item.len() as u64).to_le_bytes());
+            for f in item {
+                bytes.extend_from_slice(&f.as_canonical_u32().to_le_bytes());
+            }
+        }
+        keccak256(bytes).0
+    }
 }
 
 impl ProvingTask for UniversalProvingTask {
@@ -40,4 +76,38 @@ impl ProvingTask for UniversalProvingTask {
     fn fork_name(&self) -> ForkName {
         ForkName::from(self.fork_name.as_str())
     }
+
+    fn try_fork_name(&self) -> Result<ForkName, AdmissionError> {
+        self.fork_name
+            .parse()
+            .map_err(|_| AdmissionError::UnknownFork(self.fork_name.clone()))
+    }
+
+    fn vk(&self) -> &[u8] {
+        &self.vk
+    }
+
+    fn witness_version(&self) -> Result<Option<u8>, AdmissionError> {
+        // All witnesses are bincode-encoded structs leading with the version byte, which bincode
+        // encodes as a single byte.
+        self.serialized_witness
+            .first()
+            .and_then(|witness| witness.first())
+            .copied()
+            .map(Some)
+            .ok_or(AdmissionError::MissingWitness)
+    }
+
+    fn witness_hash(&self) -> [u8; 32] {
+        // The input commits are written to stdin as well, so they are part of the witness.
+        let mut bytes = Vec::new();
+        for witness in &self.serialized_witness {
+            bytes.extend_from_slice(&(witness.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(witness);
+        }
+        for commit in &self.input_commits {
+            bytes.extend_from_slice(commit);
+        }
+        keccak256(bytes).0
+    }
 }
@@ -132,6 +132,23 @@ pub mod as_base64 {
     }
 }
 
+/// Peak resident memory of the whole process in bytes since it started (`VmHWM`), read from
+/// `/proc/self/status`. It is shared by all proofs of the process, not specific to one.
+///
+/// Returns `None` where procfs is not available.
+pub fn process_peak_rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let kib: u64 = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kib * 1024)
+}
+
 /// Print GPU memory usage information including used, free, and total memory in GiB.
 pub fn print_gpu_memory_usage() -> Result<(), Error> {
     #[cfg(feature = "cuda")]
@@ -1,48 +1,189 @@
-use openvm_sdk::{Sdk, SdkError, StdIn, types::ExecutableFormat};
+use std::{
+    io::{Cursor, Write},
+    sync::{Arc, Mutex},
+};
+
+use openvm_circuit::{
+    arch::execution_mode::Segment, system::memory::merkle::public_values::UserPublicValuesProof,
+};
+use openvm_sdk::{SC, Sdk, StdIn, types::ExecutableFormat};
+use openvm_stark_sdk::openvm_stark_backend::{codec::Decode, proof::Proof};
+use openvm_verify_stark_host::{
+    VmStarkProof, deferral::DeferralMerkleProofs, vk::VerificationBaseline,
+};
+use scroll_zkvm_types::proof::StarkProof;
+
+use crate::Error;
 
 pub struct ExecutionResult {
     pub total_cycle: u64,
-    #[allow(dead_code)]
     pub public_values: Vec<u8>,
+    /// Why the cost-metered execution failed, if the cycles were counted by the fallback.
+    pub metering_failure: Option<String>,
+}
+
+/// Result of executing the guest program with segmentation, as done by the prover.
+pub struct SegmentedExecutionResult {
+    /// The segments of the execution, each proven by one app proof.
+    pub segments: Vec<Segment>,
+    pub public_values: Vec<u8>,
+}
+
+impl SegmentedExecutionResult {
+    /// Number of instructions executed in each segment.
+    pub fn segment_cycles(&self) -> Vec<u64> {
+        self.segments
+            .iter()
+            .map(|segment| segment.num_insns)
+            .collect()
+    }
+
+    pub fn total_cycle(&self) -> u64 {
+        self.segments.iter().map(|segment| segment.num_insns).sum()
+    }
+}
+
+/// What the guest program printed while executed, see [`execute_guest`].
+///
+/// The output is kept, e.g. to report the guest's panic message or write it into a crash
+/// bundle, and is still written to stdout. Clones share the same output.
+#[derive(Clone, Debug, Default)]
+pub struct GuestOutput(Arc<Mutex<Vec<u8>>>);
+
+impl GuestOutput {
+    /// The output printed so far.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap_or_else(|e| e.into_inner())).into_owned()
+    }
+
+    /// Forget the output printed so far, e.g. before executing the guest again.
+    fn clear(&self) {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+impl Write for GuestOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .extend_from_slice(buf);
+        std::io::stdout().write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        std::io::stdout().flush()
+    }
 }
 
 // Execute the guest program using the metered executor first to measure actual cycles.
 // If the execution exceeds the maximum cost allowed by the metered executor,
-// we re-execute the program using the normal executor, which has no limitations
-// on the size of the execution process.
+// we re-execute the program using the segmenting metered executor, which has no limitations
+// on the size of the execution process, and count the cycles over its segments.
+//
+// What the guest prints is written to `output`.
 pub fn execute_guest(
     sdk: &Sdk,
     exe: impl Into<ExecutableFormat>,
     inputs: &StdIn,
-) -> Result<ExecutionResult, SdkError> {
-    let exe = sdk.convert_to_exe(exe)?;
-    match sdk.execute_metered_cost(exe.clone(), inputs.clone()) {
+    output: &GuestOutput,
+) -> Result<ExecutionResult, Error> {
+    let exe = sdk.convert_to_exe(exe).map_err(Error::from_sdk)?;
+    let result =
+        sdk.execute_metered_cost_with_stdout(exe.clone(), inputs.clone(), Box::new(output.clone()));
+    match result {
         Ok((public_values, (_cost, instret))) => {
-            if public_values.iter().all(|&x| x == 0) {
-                return Err(SdkError::Other(eyre::eyre!(
-                    "public_values are all 0s for unexpected reason"
-                )));
-            }
+            warn_if_zero(&public_values);
             Ok(ExecutionResult {
                 total_cycle: instret,
                 public_values,
+                metering_failure: None,
             })
         }
         Err(e) => {
-            tracing::warn!("Metered execution failed: {e}, falling back to execute");
-            let public_values = sdk.execute(exe, inputs.clone())?;
-            if public_values.iter().all(|&x| x == 0) {
-                return Err(SdkError::Other(eyre::eyre!(
-                    "public_values are all 0s upon execute"
-                )));
+            let metering_failure = e.to_string();
+            let e = Error::from_guest_run(e, &output.text());
+            // Executing again does not help if the guest itself failed.
+            if matches!(
+                e,
+                Error::GuestPanic { .. } | Error::WitnessDeserialization(_)
+            ) {
+                return Err(e);
             }
-            // The fallback executor does not report instruction-retired counts.
-            // Use u64::MAX as a sentinel so any speed logging shows an obviously
-            // invalid value instead of a misleading 0 MHz.
+            tracing::warn!(
+                "Metered execution failed: {metering_failure}, falling back to segmented execution"
+            );
+            // The guest runs from the start again.
+            output.clear();
+            let exec_result = execute_guest_segmented(sdk, exe, inputs, output)?;
             Ok(ExecutionResult {
-                total_cycle: u64::MAX,
-                public_values,
+                total_cycle: exec_result.total_cycle(),
+                public_values: exec_result.public_values,
+                metering_failure: Some(metering_failure),
             })
         }
     }
 }
+
+// Execute the guest program with the metered executor, splitting the execution into the segments
+// that would be proven by the app prover. What the guest prints is written to `output`.
+pub fn execute_guest_segmented(
+    sdk: &Sdk,
+    exe: impl Into<ExecutableFormat>,
+    inputs: &StdIn,
+    output: &GuestOutput,
+) -> Result<SegmentedExecutionResult, Error> {
+    let exe = sdk.convert_to_exe(exe).map_err(Error::from_sdk)?;
+    let (public_values, segments) = sdk
+        .execute_metered_with_stdout(exe, inputs.clone(), Box::new(output.clone()))
+        .map_err(|e| Error::from_guest_run(e, &output.text()))?;
+    warn_if_zero(&public_values);
+    Ok(SegmentedExecutionResult {
+        segments,
+        public_values,
+    })
+}
+
+/// All-zero public values are legitimate for a guest, but not what our circuits reveal.
+fn warn_if_zero(public_values: &[u8]) {
+    if public_values.iter().all(|&x| x == 0) {
+        tracing::warn!("public_values are all 0s");
+    }
+}
+
+/// Decode a [`StarkProof`] into the SDK's [`VmStarkProof`] and its stored
+/// [`VerificationBaseline`].
+pub fn decode_stark_proof(
+    proof: &StarkProof,
+) -> Result<(VmStarkProof, VerificationBaseline), Error> {
+    let decoding = |what: &str, e: &dyn std::fmt::Display| {
+        Error::Custom(format!("failed to decode {what} of stark proof: {e}"))
+    };
+
+    let inner = Proof::decode_from_bytes(&proof.proof).map_err(|e| decoding("proof", &e))?;
+    let user_pvs_proof =
+        UserPublicValuesProof::decode::<SC, _>(&mut Cursor::new(&proof.user_pvs_proof))
+            .map_err(|e| decoding("user_pvs_proof", &e))?;
+    let deferral_merkle_proofs = if proof.deferral_merkle_proofs.is_empty() {
+        None
+    } else {
+        Some(
+            DeferralMerkleProofs::decode(&mut Cursor::new(&proof.deferral_merkle_proofs))
+                .map_err(|e| decoding("deferral_merkle_proofs", &e))?,
+        )
+    };
+    if proof.baseline.is_empty() {
+        return Err(decoding("baseline", &"missing (v2+ required)"));
+    }
+    let baseline = serde_json::from_slice(&proof.baseline).map_err(|e| decoding("baseline", &e))?;
+
+    Ok((
+        VmStarkProof {
+            inner,
+            user_pvs_proof,
+            deferral_merkle_proofs,
+        },
+        baseline,
+    ))
+}
@@ -50,27 +50,29 @@ impl ForkName {
 
 impl From<Option<&str>> for ForkName {
     fn from(value: Option<&str>) -> Self {
+        value.map(Self::from).unwrap_or_default()
+    }
+}
+
+impl std::str::FromStr for ForkName {
+    type Err = String;
+
+    /// Parse the fork name, failing instead of panicking on unknown forks.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
         match value {
-            None => Default::default(),
-            Some("euclidv1") => ForkName::EuclidV1,
-            Some("euclidv2") => ForkName::EuclidV2,
-            Some("feynman") => ForkName::Feynman,
-            Some("galileo") => ForkName::Galileo,
-            Some("galileov2") => ForkName::GalileoV2,
-            Some(s) => unreachable!("hardfork not accepted: {s}"),
+            "euclidv1" => Ok(ForkName::EuclidV1),
+            "euclidv2" => Ok(ForkName::EuclidV2),
+            "feynman" => Ok(ForkName::Feynman),
+            "galileo" => Ok(ForkName::Galileo),
+            "galileov2" => Ok(ForkName::GalileoV2),
+            s => Err(format!("hardfork not accepted: {s}")),
         }
     }
 }
 
 impl From<&str> for ForkName {
+    /// Same as [`str::parse`], panicking on unknown forks.
     fn from(value: &str) -> Self {
-        match value {
-            "euclidv1" => ForkName::EuclidV1,
-            "euclidv2" => ForkName::EuclidV2,
-            "feynman" => ForkName::Feynman,
-            "galileo" => ForkName::Galileo,
-            "galileov2" => ForkName::GalileoV2,
-            s => unreachable!("hardfork not accepted: {s}"),
-        }
+        value.parse().unwrap_or_else(|e| unreachable!("{e}"))
     }
 }
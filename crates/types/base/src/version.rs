@@ -174,6 +174,22 @@ impl Version {
         }
     }
 
+    /// Parse a version byte, returning `None` if it is not supported.
+    ///
+    /// Unlike [`Version::from`], this does not panic on unknown domains or STF versions.
+    pub fn try_from_version_byte(value: u8) -> Option<Self> {
+        [
+            Self::euclid_v1(),
+            Self::euclid_v2(),
+            Self::feynman(),
+            Self::galileo(),
+            Self::galileo_v2(),
+            Self::validium_v1(),
+        ]
+        .into_iter()
+        .find(|version| version.as_version_byte() == value)
+    }
+
     pub fn is_validium(&self) -> bool {
         self.domain == Domain::Validium
     }
@@ -0,0 +1,87 @@
+use std::path::Path;
+
+use openvm_sdk::config::{AggregationSystemParams, AggregationTreeConfig};
+use openvm_stark_sdk::config::{
+    internal_params_with_100_bits_security, leaf_params_with_100_bits_security,
+};
+use serde::{Deserialize, Serialize};
+
+/// File name of the serialized [`AggConfig`], written by build-guest next to each circuit's
+/// `app.vmexe` and in the `verifier` directory of a release.
+pub const FD_AGG_CONFIG: &str = "agg_config.json";
+
+/// File name of the deferral hook commit of a circuit, written by build-guest next to its
+/// `app.vmexe` as a JSON `Option<CommitBytes>`, `null` for circuits without deferral.
+///
+/// Along with `agg_vk.bin`, it is what an aggregation circuit needs of its child circuit to
+/// verify the child's proofs through deferral.
+pub const FD_DEF_HOOK_COMMIT: &str = "def_hook_commit.json";
+
+/// Aggregation parameters and aggregation-tree shape shared by the chunk, batch and bundle
+/// provers, the agg VKs and the EVM verifier of a release.
+///
+/// The agg VKs and the EVM verifier depend on this configuration, so a prover must use the one
+/// its release was built with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggConfig {
+    /// Security level, in bits, of the leaf and internal aggregation parameters.
+    pub security_bits: u32,
+    /// Number of app proofs aggregated by a leaf proof.
+    pub num_children_leaf: usize,
+    /// Number of proofs aggregated by an internal proof.
+    pub num_children_internal: usize,
+}
+
+impl Default for AggConfig {
+    fn default() -> Self {
+        Self {
+            security_bits: 100,
+            num_children_leaf: 4,
+            num_children_internal: 3,
+        }
+    }
+}
+
+impl AggConfig {
+    /// The leaf and internal aggregation parameters.
+    pub fn agg_params(&self) -> Result<AggregationSystemParams, String> {
+        match self.security_bits {
+            100 => Ok(AggregationSystemParams {
+                leaf: leaf_params_with_100_bits_security(),
+                internal: internal_params_with_100_bits_security(),
+            }),
+            bits => Err(format!(
+                "unsupported aggregation security level: {bits} bits"
+            )),
+        }
+    }
+
+    /// The shape of the aggregation tree.
+    pub fn agg_tree_config(&self) -> AggregationTreeConfig {
+        AggregationTreeConfig {
+            num_children_leaf: self.num_children_leaf,
+            num_children_internal: self.num_children_internal,
+        }
+    }
+
+    /// Read the configuration from `dir`, see [`FD_AGG_CONFIG`].
+    ///
+    /// Returns `None` if the file does not exist, e.g. for releases built before the
+    /// configuration was recorded.
+    pub fn read_from_dir(dir: impl AsRef<Path>) -> std::io::Result<Option<Self>> {
+        let path = dir.as_ref().join(FD_AGG_CONFIG);
+        match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Write the configuration to `dir`, see [`FD_AGG_CONFIG`].
+    pub fn write_to_dir(&self, dir: impl AsRef<Path>) -> std::io::Result<()> {
+        let bytes = serde_json::to_vec_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(dir.as_ref().join(FD_AGG_CONFIG), bytes)
+    }
+}
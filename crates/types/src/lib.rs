@@ -22,6 +22,8 @@ pub mod scroll {
 
 pub use types_base::{aggregation as types_agg, public_inputs, version};
 
+pub mod config;
+
 pub mod proof;
 
 pub mod task;
@@ -21,6 +21,9 @@ pub struct EvmProof {
     /// proof.
     #[serde(with = "vec_as_base64")]
     pub instances: Vec<u8>,
+    /// Stat of generating the proof, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stat: Option<StarkProofStat>,
     /*
     //pub accumulator: Vec<u8>,
     /// The public inputs of the SNARK proof.
@@ -37,7 +40,7 @@ pub struct EvmProof {
 }
 
 /// Stat for the insight of stark proofing
-#[derive(Clone, Serialize, Deserialize, Default)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
 pub struct StarkProofStat {
     /// total cycles
     pub total_cycles: u64,
@@ -45,6 +48,45 @@ pub struct StarkProofStat {
     pub execution_time_mills: u64,
     /// proving time
     pub proving_time_mills: u64,
+    /// Part of the proving time spent executing the app segments again, each from the state
+    /// the previous one ended in, to record the traces they are proven from.
+    #[serde(default)]
+    pub preflight_time_mills: u64,
+    /// Wall time of each proving phase.
+    #[serde(default)]
+    pub phase_time_mills: PhaseTimes,
+    /// Number of app segments proven, 0 if unknown.
+    #[serde(default)]
+    pub num_segments: u64,
+    /// Peak resident memory of the whole prover process since it started, 0 if unknown. This is
+    /// the process' high-water mark when the proof finished, not the memory used by the proof:
+    /// it includes the proofs generated before and alongside it.
+    #[serde(default, alias = "peak_rss_bytes")]
+    pub process_peak_rss_bytes: u64,
+    /// Git version of the prover.
+    #[serde(default)]
+    pub prover_version: String,
+    /// Name of the guest release the proof was generated with, empty if not configured.
+    #[serde(default)]
+    pub guest_release: String,
+}
+
+/// Wall time spent in each phase of proving, in milliseconds.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct PhaseTimes {
+    /// Deferral proofs of aggregated child proofs.
+    pub deferral: u64,
+    /// App proofs of all segments.
+    pub app: u64,
+    /// Leaf aggregation layer.
+    pub leaf: u64,
+    /// Internal aggregation layers.
+    pub internal: u64,
+    /// Root proof.
+    pub root: u64,
+    /// Halo2 wrapping into an EVM proof.
+    pub halo2: u64,
 }
 
 /// Helper to modify serde implementations on the remote [`RootProof`] type.
@@ -109,6 +151,7 @@ impl From<OpenVmEvmProof> for EvmProof {
         Self {
             proof: raw_proof.proof,
             instances,
+            stat: None,
         }
     }
 }
@@ -165,6 +208,45 @@ impl From<EvmProof> for ProofEnum {
     }
 }
 
+impl StarkProof {
+    /// Derive public inputs from the user public values proof.
+    pub fn public_values(&self) -> Vec<u32> {
+        use openvm_circuit::system::memory::merkle::public_values::UserPublicValuesProof;
+        use openvm_stark_sdk::config::baby_bear_poseidon2::{DIGEST_SIZE, F};
+        let proof: UserPublicValuesProof<DIGEST_SIZE, F> =
+            UserPublicValuesProof::decode::<SC, _>(&mut Cursor::new(&self.user_pvs_proof))
+                .expect("decode user_pvs_proof failed");
+        proof
+            .public_values
+            .iter()
+            .map(|x: &F| x.as_canonical_u32())
+            .collect::<Vec<u32>>()
+    }
+}
+
+impl EvmProof {
+    /// Derive public inputs from the instances.
+    pub fn public_values(&self) -> Vec<u32> {
+        // The first 12 scalars are accumulators.
+        // The next 2 scalars are digests.
+        // The next 32 scalars are the public input hash.
+        let pi_hash_bytes = self
+            .instances
+            .iter()
+            .skip(14 * 32)
+            .take(32 * 32)
+            .cloned()
+            .collect::<Vec<u8>>();
+
+        // The 32 scalars of public input hash actually only have the LSB that is the
+        // meaningful byte.
+        pi_hash_bytes
+            .chunks_exact(32)
+            .map(|bytes32_chunk| bytes32_chunk[31] as u32)
+            .collect::<Vec<u32>>()
+    }
+}
+
 impl ProofEnum {
     /// Get the stark proof as reference.
     pub fn as_stark_proof(&self) -> Option<&StarkProof> {
@@ -203,40 +285,8 @@ impl ProofEnum {
     /// Derive public inputs from the proof.
     pub fn public_values(&self) -> Vec<u32> {
         match self {
-            Self::Stark(stark_proof) => {
-                // Decode user_pvs_proof to extract public values
-                use openvm_circuit::system::memory::merkle::public_values::UserPublicValuesProof;
-                use openvm_stark_sdk::config::baby_bear_poseidon2::{DIGEST_SIZE, F};
-                let proof: UserPublicValuesProof<DIGEST_SIZE, F> =
-                    UserPublicValuesProof::decode::<SC, _>(&mut Cursor::new(
-                        &stark_proof.user_pvs_proof,
-                    ))
-                    .expect("decode user_pvs_proof failed");
-                proof
-                    .public_values
-                    .iter()
-                    .map(|x: &F| x.as_canonical_u32())
-                    .collect::<Vec<u32>>()
-            }
-            Self::Evm(evm_proof) => {
-                // The first 12 scalars are accumulators.
-                // The next 2 scalars are digests.
-                // The next 32 scalars are the public input hash.
-                let pi_hash_bytes = evm_proof
-                    .instances
-                    .iter()
-                    .skip(14 * 32)
-                    .take(32 * 32)
-                    .cloned()
-                    .collect::<Vec<u8>>();
-
-                // The 32 scalars of public input hash actually only have the LSB that is the
-                // meaningful byte.
-                pi_hash_bytes
-                    .chunks_exact(32)
-                    .map(|bytes32_chunk| bytes32_chunk[31] as u32)
-                    .collect::<Vec<u32>>()
-            }
+            Self::Stark(stark_proof) => stark_proof.public_values(),
+            Self::Evm(evm_proof) => evm_proof.public_values(),
         }
     }
 }
@@ -1,11 +1,9 @@
 use once_cell::sync::Lazy;
 use openvm_sdk::Sdk;
-use openvm_sdk::config::AggregationSystemParams;
 use openvm_sdk::keygen::AggProvingKey;
-use openvm_stark_sdk::config::{
-    MAX_APP_LOG_STACKED_HEIGHT, app_params_with_100_bits_security,
-    internal_params_with_100_bits_security, leaf_params_with_100_bits_security,
-};
+use openvm_stark_sdk::config::{MAX_APP_LOG_STACKED_HEIGHT, app_params_with_100_bits_security};
+
+use crate::config::AggConfig;
 
 /// Proving key for STARK aggregation. Primarily used to aggregate
 /// [continuation proofs][openvm_sdk::prover::vm::ContinuationVmProof].
@@ -13,9 +11,8 @@ pub static AGG_STARK_PROVING_KEY: Lazy<AggProvingKey> = Lazy::new(build_agg_pk);
 
 fn build_agg_pk() -> AggProvingKey {
     let app_params = app_params_with_100_bits_security(MAX_APP_LOG_STACKED_HEIGHT);
-    let agg_params = AggregationSystemParams {
-        leaf: leaf_params_with_100_bits_security(),
-        internal: internal_params_with_100_bits_security(),
-    };
+    let agg_params = AggConfig::default()
+        .agg_params()
+        .expect("default aggregation config is supported");
     Sdk::riscv32(app_params, agg_params).agg_pk().clone()
 }
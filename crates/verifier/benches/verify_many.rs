@@ -0,0 +1,81 @@
+//! Throughput of verifying STARK proofs strictly one at a time with
+//! [`UniversalVerifier::verify_stark_proof_strict`], and in parallel with
+//! [`UniversalVerifier::verify_many`].
+//!
+//! Needs v2 released assets, which `./testdata` does not hold: the verifier directory in
+//! `VERIFIER_DIR`, and a STARK proof of its release with its vk in `VERIFIER_PROOF`. Nothing is
+//! measured without them. The proof is verified `VERIFIER_NUM_PROOFS` times per iteration, 32 by
+//! default.
+//!
+//! ```shell
+//! VERIFIER_DIR=<release>/verifier VERIFIER_PROOF=<chunk-proof.json> \
+//!     cargo bench -p scroll-zkvm-verifier --bench verify_many
+//! ```
+
+use std::path::PathBuf;
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use scroll_zkvm_types::{proof::ProofEnum, utils::vec_as_base64};
+use scroll_zkvm_verifier::verifier::UniversalVerifier;
+
+/// A proof along with the vk of its circuit, as in `./testdata/proofs`.
+#[derive(serde::Deserialize)]
+struct WrappedProof {
+    proof: ProofEnum,
+    #[serde(with = "vec_as_base64", default)]
+    vk: Vec<u8>,
+}
+
+fn env_or(name: &str, default: &str) -> String {
+    std::env::var(name).unwrap_or_else(|_| default.to_string())
+}
+
+fn bench_verify_many(c: &mut Criterion) {
+    let (Ok(dir_verifier), Ok(path_proof)) = (
+        std::env::var("VERIFIER_DIR"),
+        std::env::var("VERIFIER_PROOF"),
+    ) else {
+        eprintln!("skipping verify_many: VERIFIER_DIR and VERIFIER_PROOF are not set");
+        return;
+    };
+    let (dir_verifier, path_proof) = (PathBuf::from(dir_verifier), PathBuf::from(path_proof));
+    let num_proofs: usize = env_or("VERIFIER_NUM_PROOFS", "32")
+        .parse()
+        .expect("VERIFIER_NUM_PROOFS must be a number");
+
+    let verifier = UniversalVerifier::setup(&dir_verifier).expect("failed to load the verifier");
+    let proof: WrappedProof = serde_json::from_reader(std::io::BufReader::new(
+        std::fs::File::open(&path_proof).expect("failed to open the proof"),
+    ))
+    .expect("failed to read the proof");
+    let stark_proof = proof
+        .proof
+        .as_stark_proof()
+        .expect("not a STARK proof")
+        .clone();
+    let proofs = vec![(stark_proof, proof.vk); num_proofs];
+
+    let mut group = c.benchmark_group("verify_stark_proofs");
+    group.throughput(Throughput::Elements(num_proofs as u64));
+    group.sample_size(10);
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            for (stark_proof, vk) in &proofs {
+                verifier
+                    .verify_stark_proof_strict(stark_proof, vk)
+                    .expect("invalid proof");
+            }
+        })
+    });
+    group.bench_function("verify_many", |b| {
+        b.iter(|| {
+            for result in verifier.verify_many(&proofs) {
+                result.expect("invalid proof");
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_verify_many);
+criterion_main!(benches);
@@ -0,0 +1,23 @@
+/// Error of verifying a proof against the public inputs it is expected to prove, see
+/// [`check_pi_hash`][crate::verifier::check_pi_hash].
+#[derive(Debug, thiserror::Error)]
+pub enum VerifyError {
+    /// The proof is not a valid proof of the program.
+    #[error("invalid proof: {0}")]
+    InvalidProof(eyre::Report),
+    /// The proof does not reveal a pi_hash.
+    #[error("proof reveals {len} public values, expected a 32-byte pi_hash")]
+    MissingPiHash { len: usize },
+    /// The proof is a valid proof of the program, but for other public inputs.
+    #[error(
+        "pi_hash mismatch: proof 0x{}, expected 0x{}",
+        hex::encode(proof),
+        hex::encode(expected)
+    )]
+    PiHashMismatch {
+        /// The pi_hash of the expected public inputs.
+        expected: [u8; 32],
+        /// The pi_hash revealed by the proof.
+        proof: [u8; 32],
+    },
+}
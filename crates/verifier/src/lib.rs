@@ -1,5 +1,8 @@
 pub mod commitments;
 
+pub mod error;
+pub use error::VerifyError;
+
 pub mod evm;
 
 pub mod verifier;
@@ -1,24 +1,38 @@
 use openvm_continuations::CommitBytes;
 use openvm_sdk::SC;
 use openvm_sdk::Sdk;
-use openvm_sdk::types::AppExecutionCommit;
 use openvm_stark_sdk::openvm_stark_backend::keygen::types::MultiStarkVerifyingKey;
 use openvm_stark_sdk::openvm_stark_backend::{codec::Decode, p3_field::PrimeField32, proof::Proof};
-use scroll_zkvm_types::proof::OpenVmEvmProof;
+use openvm_verify_stark_host::{
+    VmStarkProof, verify_vm_stark_proof_decoded,
+    vk::{VerificationBaseline, VmStarkVerifyingKey},
+};
+use rayon::prelude::*;
+use scroll_zkvm_types::config::FD_DEF_HOOK_COMMIT;
+use scroll_zkvm_types::proof::{EvmProof, OpenVmEvmProof};
+use scroll_zkvm_types::public_inputs::{MultiVersionPublicInputs, Version};
 use scroll_zkvm_types::{proof::StarkProof, utils::serialize_vk};
+use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Arc;
+
+use crate::VerifyError;
 
 pub use scroll_zkvm_types::zkvm::AGG_STARK_PROVING_KEY;
 
 /// Verifier capable of checking both STARK and EVM (Halo2 SNARK) proofs.
 ///
-/// Loads the aggregation verifying key (`loaded_mvk`) and EVM verifier bytecode
-/// (`evm_verifier`) from the release directory at setup time.
+/// Loads the aggregation verifying key (`loaded_mvk`), EVM verifier bytecode
+/// (`evm_verifier`) and deferral hook commits (`def_hook_commits`) from the release directory
+/// at setup time.
 pub struct UniversalVerifier {
     /// EVM verifier bytecode deployed to simulate on-chain verification.
     pub evm_verifier: Vec<u8>,
     /// Aggregation STARK verifying key used to verify STARK proofs.
-    pub loaded_mvk: MultiStarkVerifyingKey<SC>,
+    pub loaded_mvk: Arc<MultiStarkVerifyingKey<SC>>,
+    /// Deferral hook commit of each circuit of the release, by app vk, used by the strict
+    /// verification of STARK proofs.
+    pub def_hook_commits: HashMap<Vec<u8>, Option<CommitBytes>>,
 }
 
 impl UniversalVerifier {
@@ -27,10 +41,10 @@ impl UniversalVerifier {
     /// # Commitment check
     /// The `vk` bytes encode the expected program commitment (`exe` + `vm`).
     /// We compare the `exe` commitment against the proof baseline to ensure the
-    /// proof was generated by the expected guest program. The `vm` commitment is
-    /// checked explicitly in [`verify_evm_proof`] because the EVM proof carries
-    /// the full `AppExecutionCommit`; for STARK proofs, the remaining baseline
-    /// fields (including `app_vk_commit`) are validated by [`Sdk::verify_proof`].
+    /// proof was generated by the expected guest program. The remaining baseline
+    /// fields are taken from the proof as is, use
+    /// [`verify_stark_proof_strict_with_vk`][Self::verify_stark_proof_strict_with_vk]
+    /// for proofs of untrusted provers.
     ///
     /// # Deferral support
     /// OpenVM v2+ proofs may contain `deferral_merkle_proofs`. If present they
@@ -40,57 +54,68 @@ impl UniversalVerifier {
         stark_proof: &StarkProof,
         vk: &[u8],
     ) -> eyre::Result<()> {
-        let prog_commit = serialize_vk::deserialize(vk);
+        let (vm_stark_proof, baseline) = decode_stark_proof(stark_proof)?;
+        check_exe_commit(vk, &baseline)?;
 
-        use openvm_verify_stark_host::{VmStarkProof, vk::VerificationBaseline};
-        let baseline: VerificationBaseline = if stark_proof.baseline.is_empty() {
-            eyre::bail!("stark proof missing verification baseline (v2+ required)");
-        } else {
-            serde_json::from_slice(&stark_proof.baseline)?
-        };
+        Sdk::verify_proof(agg_mvk.clone(), baseline, &vm_stark_proof)?;
 
-        let deferral_merkle_proofs = if stark_proof.deferral_merkle_proofs.is_empty() {
-            None
-        } else {
-            Some(
-                openvm_verify_stark_host::deferral::DeferralMerkleProofs::decode(
-                    &mut std::io::Cursor::new(&stark_proof.deferral_merkle_proofs),
-                )?,
-            )
-        };
-        let vm_stark_proof = VmStarkProof {
-            inner: Proof::<SC>::decode_from_bytes(&stark_proof.proof)?,
-            user_pvs_proof: {
-                use openvm_circuit::system::memory::merkle::public_values::UserPublicValuesProof;
-                UserPublicValuesProof::decode::<SC, _>(&mut std::io::Cursor::new(
-                    &stark_proof.user_pvs_proof,
-                ))?
-            },
-            deferral_merkle_proofs,
-        };
-        let _expected_app_commit = AppExecutionCommit {
-            app_exe_commit: CommitBytes::from(prog_commit.exe),
-            app_vm_commit: CommitBytes::from(prog_commit.vm),
-        };
-        // Compare the expected program commitment (from vk) against the proof baseline.
-        // The baseline is derived from the STARK proof's public inputs / commitments.
-        let baseline_exe_commit: [u32; 8] = baseline.app_exe_commit.map(|f| f.as_canonical_u32());
-        if prog_commit.exe != baseline_exe_commit {
-            eyre::bail!("stark: mismatch EXE commitment");
-        }
+        Ok(())
+    }
 
-        Sdk::verify_proof(agg_mvk.clone(), baseline, &vm_stark_proof)?;
+    /// Same as [`verify_stark_proof_with_vk`][Self::verify_stark_proof_with_vk], without
+    /// trusting the verification baseline embedded in the proof.
+    ///
+    /// The expected baseline is rebuilt from trusted material instead, see
+    /// [`expected_baseline`][Self::expected_baseline]: the app exe and vm commits from `vk`,
+    /// and the deferral hook commit of the circuit from its release assets, see
+    /// [`read_def_hook_commit`]. A proof whose embedded baseline differs in any field is
+    /// rejected, and the proof is verified against the expected baseline.
+    pub fn verify_stark_proof_strict_with_vk(
+        agg_mvk: &MultiStarkVerifyingKey<SC>,
+        stark_proof: &StarkProof,
+        vk: &[u8],
+        def_hook_commit: Option<CommitBytes>,
+    ) -> eyre::Result<()> {
+        let (vm_stark_proof, baseline) = decode_stark_proof(stark_proof)?;
+        let expected = Self::expected_baseline(vk, def_hook_commit);
+        check_baseline(&baseline, &expected)?;
+
+        Sdk::verify_proof(agg_mvk.clone(), expected, &vm_stark_proof)?;
 
         Ok(())
     }
 
+    /// The verification baseline of the proofs of the circuit with `vk`, whose deferral hook
+    /// commit is `def_hook_commit`, `None` for circuits without deferral.
+    ///
+    /// The app vk commit of the baseline is the vm commit of `vk`: releases derive the vm
+    /// commit from the app vk of the circuit, the way the baseline does, see
+    /// `scroll_zkvm_prover::setup::app_vm_commit`. The integration tests check that the
+    /// baseline of real proofs is the expected one, and that a proof tampering either commit is
+    /// rejected.
+    pub fn expected_baseline(
+        vk: &[u8],
+        def_hook_commit: Option<CommitBytes>,
+    ) -> VerificationBaseline {
+        let prog_commit = serialize_vk::deserialize(vk);
+        VerificationBaseline {
+            app_exe_commit: CommitBytes::from(prog_commit.exe).into(),
+            app_vk_commit: CommitBytes::from(prog_commit.vm).into(),
+            def_hook_commit: def_hook_commit.map(Into::into),
+        }
+    }
+
     /// Load verifier assets from a release directory.
     ///
     /// Expects:
     /// - `{path_verifier}/verifier.bin` — EVM verifier bytecode
     /// - `{path_verifier}/root_verifier_vk` — serialized aggregation VK
+    /// - `{path_verifier}/openVmVk.json` — app vks of the circuits, whose deferral hook commits
+    ///   are read from the circuit directories next to the verifier, see
+    ///   [`read_def_hook_commit`]
     ///
-    /// Falls back to computing the VK on-the-fly if the file is missing (slow).
+    /// Falls back to computing the VK on-the-fly if the file is missing (slow). Without
+    /// `openVmVk.json`, no deferral hook commit is known and strict verification fails.
     pub fn setup<P: AsRef<Path>>(path_verifier: P) -> eyre::Result<Self> {
         let path_verifier_code = path_verifier.as_ref().join("verifier.bin");
         let path_agg_vk = path_verifier.as_ref().join("root_verifier_vk");
@@ -101,9 +126,22 @@ impl UniversalVerifier {
             AGG_STARK_PROVING_KEY.internal_recursive.get_vk().clone()
         });
 
+        let def_hook_commits = read_def_hook_commits(path_verifier.as_ref())?;
+
         Ok(Self {
             evm_verifier,
-            loaded_mvk,
+            loaded_mvk: Arc::new(loaded_mvk),
+            def_hook_commits,
+        })
+    }
+
+    /// The deferral hook commit of the circuit with `vk`, as loaded at setup.
+    pub fn def_hook_commit(&self, vk: &[u8]) -> eyre::Result<Option<CommitBytes>> {
+        self.def_hook_commits.get(vk).cloned().ok_or_else(|| {
+            eyre::eyre!(
+                "no deferral hook commit loaded for the circuit of vk {}",
+                hex::encode(vk)
+            )
         })
     }
 
@@ -111,6 +149,109 @@ impl UniversalVerifier {
         Self::verify_stark_proof_with_vk(&self.loaded_mvk, stark_proof, vk)
     }
 
+    /// Verify a STARK proof strictly, see
+    /// [`verify_stark_proof_strict_with_vk`][Self::verify_stark_proof_strict_with_vk], with the
+    /// deferral hook commit of the circuit loaded at setup, see
+    /// [`def_hook_commit`][Self::def_hook_commit].
+    pub fn verify_stark_proof_strict(
+        &self,
+        stark_proof: &StarkProof,
+        vk: &[u8],
+    ) -> eyre::Result<()> {
+        let def_hook_commit = self.def_hook_commit(vk)?;
+        Self::verify_stark_proof_strict_with_vk(&self.loaded_mvk, stark_proof, vk, def_hook_commit)
+    }
+
+    /// Verify STARK proofs in parallel, each against its vk, strictly, see
+    /// [`verify_stark_proof_strict`][Self::verify_stark_proof_strict]. Returns the result of
+    /// each proof, in the order of `proofs`.
+    ///
+    /// The proofs are grouped by vk, and the expected baseline of each group is rebuilt once.
+    /// Groups are verified one after another, the proofs of a group in parallel, against a
+    /// single copy of the aggregation VK whose baseline is swapped between groups.
+    pub fn verify_many(&self, proofs: &[(StarkProof, Vec<u8>)]) -> Vec<eyre::Result<()>> {
+        // Indices of the proofs of each vk, in the order the vks first appear.
+        let mut groups: Vec<(&[u8], Vec<usize>)> = Vec::new();
+        for (i, (_, vk)) in proofs.iter().enumerate() {
+            match groups
+                .iter_mut()
+                .find(|(group_vk, _)| *group_vk == vk.as_slice())
+            {
+                Some((_, indices)) => indices.push(i),
+                None => groups.push((vk, vec![i])),
+            }
+        }
+
+        let mut results: Vec<eyre::Result<()>> = proofs.iter().map(|_| Ok(())).collect();
+        let mut shared_vk: Option<VmStarkVerifyingKey> = None;
+        for (vk, indices) in groups {
+            let expected = match self.def_hook_commit(vk) {
+                Ok(def_hook_commit) => Self::expected_baseline(vk, def_hook_commit),
+                Err(e) => {
+                    for i in indices {
+                        results[i] = Err(eyre::eyre!("{e}"));
+                    }
+                    continue;
+                }
+            };
+            match &mut shared_vk {
+                Some(shared_vk) => shared_vk.baseline = expected,
+                None => {
+                    shared_vk = Some(VmStarkVerifyingKey {
+                        mvk: (*self.loaded_mvk).clone(),
+                        baseline: expected,
+                    })
+                }
+            }
+            let shared_vk = shared_vk.as_ref().expect("set above");
+
+            let group_results: Vec<eyre::Result<()>> = indices
+                .par_iter()
+                .map(|&i| {
+                    let (vm_stark_proof, baseline) = decode_stark_proof(&proofs[i].0)?;
+                    check_baseline(&baseline, &shared_vk.baseline)?;
+                    verify_vm_stark_proof_decoded(shared_vk, &vm_stark_proof)
+                        .map_err(|e| eyre::eyre!("stark: verification failed: {e}"))
+                })
+                .collect();
+            for (i, result) in indices.into_iter().zip(group_results) {
+                results[i] = result;
+            }
+        }
+        results
+    }
+
+    /// Verify a STARK proof strictly, see
+    /// [`verify_stark_proof_strict`][Self::verify_stark_proof_strict], and that it proves the
+    /// `expected` public inputs of `version`, see [`check_pi_hash`].
+    pub fn verify_stark_proof_with_pi(
+        &self,
+        stark_proof: &StarkProof,
+        vk: &[u8],
+        expected: &impl MultiVersionPublicInputs,
+        version: Version,
+    ) -> Result<(), VerifyError> {
+        self.verify_stark_proof_strict(stark_proof, vk)
+            .map_err(VerifyError::InvalidProof)?;
+        check_pi_hash(&stark_proof.public_values(), expected, version)
+    }
+
+    /// Verify an EVM proof, see [`verify_evm_proof`][Self::verify_evm_proof], and that it proves
+    /// the `expected` public inputs of `version`, see [`check_pi_hash`]. Returns the gas used.
+    pub fn verify_evm_proof_with_pi(
+        &self,
+        evm_proof: &EvmProof,
+        vk: &[u8],
+        expected: &impl MultiVersionPublicInputs,
+        version: Version,
+    ) -> Result<u64, VerifyError> {
+        let gas = self
+            .verify_evm_proof(&evm_proof.clone().into(), vk)
+            .map_err(VerifyError::InvalidProof)?;
+        check_pi_hash(&evm_proof.public_values(), expected, version)?;
+        Ok(gas)
+    }
+
     /// Verify an EVM (Halo2 SNARK) proof.
     ///
     /// # Commitment check
@@ -140,6 +281,146 @@ impl UniversalVerifier {
     }
 }
 
+/// Check that the `public_values` of a proof reveal the pi_hash of the `expected` public inputs
+/// of `version`, e.g. the `ChunkInfo`, `BatchInfo` or `BundleInfo` of the task.
+///
+/// This does not verify the proof itself.
+pub fn check_pi_hash(
+    public_values: &[u32],
+    expected: &impl MultiVersionPublicInputs,
+    version: Version,
+) -> Result<(), VerifyError> {
+    let pi_hash: [u8; 32] = public_values
+        .get(..32)
+        .and_then(public_values_as_bytes)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(VerifyError::MissingPiHash {
+            len: public_values.len(),
+        })?;
+    let expected = expected.pi_hash_by_version(version).0;
+    if pi_hash != expected {
+        return Err(VerifyError::PiHashMismatch {
+            expected,
+            proof: pi_hash,
+        });
+    }
+    Ok(())
+}
+
+/// The bytes revealed by the guest as `public_values`, each value holding a byte. `None` if a
+/// value is not a byte.
+pub fn public_values_as_bytes(public_values: &[u32]) -> Option<Vec<u8>> {
+    public_values
+        .iter()
+        .map(|&v| u8::try_from(v).ok())
+        .collect()
+}
+
+/// Read the deferral hook commit of a circuit from its release assets in `dir_circuit`, see
+/// [`FD_DEF_HOOK_COMMIT`].
+pub fn read_def_hook_commit<P: AsRef<Path>>(dir_circuit: P) -> eyre::Result<Option<CommitBytes>> {
+    let path = dir_circuit.as_ref().join(FD_DEF_HOOK_COMMIT);
+    let bytes =
+        std::fs::read(&path).map_err(|e| eyre::eyre!("failed to read {}: {e}", path.display()))?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// Read the deferral hook commits of the circuits of a release, by app vk: the vks from
+/// `openVmVk.json` in `dir_verifier`, and the commits from the circuit directories next to it.
+/// Circuits without a `def_hook_commit.json` are skipped.
+fn read_def_hook_commits(
+    dir_verifier: &Path,
+) -> eyre::Result<HashMap<Vec<u8>, Option<CommitBytes>>> {
+    let path_vks = dir_verifier.join("openVmVk.json");
+    if !path_vks.exists() {
+        tracing::warn!(
+            "{} not found, no deferral hook commit loaded",
+            path_vks.display()
+        );
+        return Ok(HashMap::new());
+    }
+    let vks: HashMap<String, String> = serde_json::from_slice(&std::fs::read(&path_vks)?)?;
+    let dir_release = dir_verifier.parent().unwrap_or(Path::new("."));
+
+    let mut def_hook_commits = HashMap::new();
+    for (key, vk) in vks {
+        let name = key.strip_suffix("_vk").unwrap_or(&key);
+        let dir_circuit = dir_release.join(name);
+        if !dir_circuit.join(FD_DEF_HOOK_COMMIT).exists() {
+            continue;
+        }
+        let vk = hex::decode(&vk)
+            .map_err(|e| eyre::eyre!("{}: vk of circuit {name}: {e}", path_vks.display()))?;
+        def_hook_commits.insert(vk, read_def_hook_commit(&dir_circuit)?);
+    }
+    Ok(def_hook_commits)
+}
+
+/// Check that the exe commit of `baseline`, embedded in a proof, is the one of `vk`.
+fn check_exe_commit(vk: &[u8], baseline: &VerificationBaseline) -> eyre::Result<()> {
+    let prog_commit = serialize_vk::deserialize(vk);
+    let baseline_exe_commit: [u32; 8] = baseline.app_exe_commit.map(|f| f.as_canonical_u32());
+    if prog_commit.exe != baseline_exe_commit {
+        eyre::bail!("stark: mismatch EXE commitment");
+    }
+    Ok(())
+}
+
+/// Check that `baseline`, embedded in a proof, is the `expected` one in every field.
+fn check_baseline(
+    baseline: &VerificationBaseline,
+    expected: &VerificationBaseline,
+) -> eyre::Result<()> {
+    // Destructured so that a field added to the baseline must be checked here too.
+    let VerificationBaseline {
+        app_exe_commit,
+        app_vk_commit,
+        def_hook_commit,
+    } = baseline;
+    if app_exe_commit != &expected.app_exe_commit {
+        eyre::bail!("stark: mismatch EXE commitment in the proof baseline");
+    }
+    if app_vk_commit != &expected.app_vk_commit {
+        eyre::bail!("stark: mismatch app VK commitment in the proof baseline");
+    }
+    if def_hook_commit != &expected.def_hook_commit {
+        eyre::bail!("stark: mismatch deferral hook commitment in the proof baseline");
+    }
+    Ok(())
+}
+
+/// Decode the OpenVM proof and the verification baseline embedded in `stark_proof`.
+fn decode_stark_proof(
+    stark_proof: &StarkProof,
+) -> eyre::Result<(VmStarkProof, VerificationBaseline)> {
+    let baseline: VerificationBaseline = if stark_proof.baseline.is_empty() {
+        eyre::bail!("stark proof missing verification baseline (v2+ required)");
+    } else {
+        serde_json::from_slice(&stark_proof.baseline)?
+    };
+
+    let deferral_merkle_proofs = if stark_proof.deferral_merkle_proofs.is_empty() {
+        None
+    } else {
+        Some(
+            openvm_verify_stark_host::deferral::DeferralMerkleProofs::decode(
+                &mut std::io::Cursor::new(&stark_proof.deferral_merkle_proofs),
+            )?,
+        )
+    };
+    let vm_stark_proof = VmStarkProof {
+        inner: Proof::<SC>::decode_from_bytes(&stark_proof.proof)?,
+        user_pvs_proof: {
+            use openvm_circuit::system::memory::merkle::public_values::UserPublicValuesProof;
+            UserPublicValuesProof::decode::<SC, _>(&mut std::io::Cursor::new(
+                &stark_proof.user_pvs_proof,
+            ))?
+        },
+        deferral_merkle_proofs,
+    };
+    Ok((vm_stark_proof, baseline))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::test::WrappedProof;
@@ -195,6 +476,40 @@ mod tests {
         Ok(())
     }
 
+    /// Strict verification needs a v2 proof, which `./testdata` does not hold: the verifier
+    /// directory and a STARK proof of its release are read from `VERIFIER_DIR` and
+    /// `VERIFIER_PROOF`.
+    #[ignore = "need v2 released assets, see VERIFIER_DIR and VERIFIER_PROOF"]
+    #[test]
+    fn verify_many_proofs() -> eyre::Result<()> {
+        let (Ok(dir_verifier), Ok(path_proof)) = (
+            std::env::var("VERIFIER_DIR"),
+            std::env::var("VERIFIER_PROOF"),
+        ) else {
+            println!("skipping verify_many_proofs: VERIFIER_DIR and VERIFIER_PROOF are not set");
+            return Ok(());
+        };
+        let verifier = UniversalVerifier::setup(Path::new(&dir_verifier))?;
+        let proof = WrappedProof::from_json(path_proof)?;
+        let stark_proof = proof.proof.as_stark_proof().unwrap().clone();
+        // The proof against a vk of another circuit.
+        let mut other_vk = proof.vk.clone();
+        other_vk[0] ^= 1;
+        let proofs = vec![
+            (stark_proof.clone(), proof.vk.clone()),
+            (stark_proof.clone(), proof.vk),
+            (stark_proof, other_vk),
+        ];
+
+        let results = verifier.verify_many(&proofs);
+        assert_eq!(results.len(), proofs.len());
+        assert!(results[0].is_ok(), "{:?}", results[0]);
+        assert!(results[1].is_ok(), "{:?}", results[1]);
+        assert!(results[2].is_err());
+
+        Ok(())
+    }
+
     #[ignore = "need euclid released assets"]
     #[test]
     fn verify_bundle_proof() -> eyre::Result<()> {